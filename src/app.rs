@@ -1,22 +1,53 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error;
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use reqwest::Client;
 use ratatui::widgets::ListState;
 use tokio::sync::mpsc::UnboundedSender;
+use crate::calendar::{shift_month, Date};
+use crate::clipboard::copy_to_clipboard;
+use crate::command::{complete_command, parse_command, AppCommand};
+use crate::config::Config;
 use crate::event::Event;
+use crate::history::{
+    load_bookmarks, load_first_difficulties, load_owns_snapshots, load_points_history, load_wishlist, load_writeup_urls,
+    record_owns_snapshot, save_bookmarks, save_first_difficulties, save_owns_snapshots, save_points_history, save_wishlist,
+    save_writeup_urls, unix_now, weekly_deltas, OwnsSnapshot, PointsEntry,
+};
+use crate::http::HTBClient;
+use crate::themes::Theme;
 
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 const HTB_API_URL: &str = "https://labs.hackthebox.com/api/v4";
+const FLAG_SUBMISSION_COOLDOWN: Duration = Duration::from_secs(2);
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+const CLIPBOARD_HISTORY_LIMIT: usize = 10;
+const SOLVE_TREND_WEEKS: usize = 8;
+const UNDO_HISTORY_LIMIT: usize = 10;
+const REACHABILITY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const PENDING_ACTION_EXPIRY: Duration = Duration::from_secs(5 * 60);
+const DETAIL_FETCH_DELAY: Duration = Duration::from_millis(250); // Selection must stay put this long
+pub const SESSION_LIMIT: Duration = Duration::from_secs(8 * 60 * 60); // Typical HTB session length
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Root {
-    pub data: Vec<Machine>,
+    pub data: Vec<MachineSummary>,
     pub links: Link
 }
 
+// Page as returned by API, machines are validated one by one into Root
+#[derive(Debug, Deserialize)]
+struct RawRoot {
+    data: Vec<Value>,
+    links: Link,
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Link {
@@ -26,9 +57,10 @@ pub struct Link {
     pub next: Option<String>,
 }
 
+// Fields needed for machine list, profile-only fields live in MachineDetail
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Machine {
+pub struct MachineSummary {
     // Add more fields as needed
     pub id: u64,
     pub name: String,
@@ -45,9 +77,17 @@ pub struct Machine {
     pub auth_user_in_root_owns: bool,
     pub active: Value,
     pub ip: Option<String>,
+    #[serde(default)]
+    pub original_difficulty: Option<u64>, // Difficulty at release, or when first listed by htb-tui
+    #[serde(skip)]
+    pub retired: bool, // Set from the list machine was fetched from
+    #[serde(skip_deserializing)]
+    pub category: Option<String>, // Filled in from machine profile
+    #[serde(skip_deserializing)]
+    pub server_region: Option<String>, // e.g. "EU", from machine profile when reported
 }
 
-impl Machine {
+impl MachineSummary {
     pub fn is_active(&self) -> bool {
         match &self.active {
             Value::Bool(b) => *b,
@@ -55,14 +95,112 @@ impl Machine {
             _ => false
         }
     }
+
+    pub fn release_parsed(&self) -> Option<Date> {
+        Date::parse(&self.release)
+    }
+
+    // Reject entries with values outside of what HTB API should return
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id == 0 {
+            return Err("id is 0".to_string());
+        }
+        if self.difficulty > 100 {
+            return Err(format!("difficulty {} is above 100", self.difficulty));
+        }
+        if !(0.0..=5.0).contains(&self.star) {
+            return Err(format!("star rating {} is outside 0-5", self.star));
+        }
+        if self.name.len() > 100 {
+            return Err("name is longer than 100 characters".to_string());
+        }
+        Ok(())
+    }
+
+    // Change of community difficulty rating since release
+    pub fn difficulty_drift(&self) -> Option<i64> {
+        self.original_difficulty
+            .map(|original| self.difficulty as i64 - original as i64)
+    }
+}
+
+// Machine profile, fetched lazily when machine is selected
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineDetail {
+    #[serde(default)]
+    pub difficulty_text: Option<String>,
+    #[serde(default)]
+    pub synopsis: Option<String>,
+    #[serde(default)]
+    pub maker: Option<Maker>,
+    #[serde(default, rename = "server_region")]
+    pub server_region: Option<String>,
+    #[serde(default, rename = "category_name")]
+    pub category: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Maker {
+    pub id: u64,
+    pub name: String,
+}
+
+// Global HTB standing from user profile
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserStats {
+    pub name: String,
+    pub rank: String,
+    pub points: u64,
+    pub user_owns: u64,
+    pub system_owns: u64,
+}
+
+// Badge or trophy from user profile
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserAchievement {
+    pub name: String,
+    pub date: String,
+    pub icon_code: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyTier {
+    Easy,
+    Medium,
+    Hard,
+    Insane,
+}
+
+impl DifficultyTier {
+    // Bucket community difficulty rating (0-100) into tiers
+    pub fn from_difficulty(difficulty: u64) -> Self {
+        match difficulty {
+            0..=25 => DifficultyTier::Easy,
+            26..=50 => DifficultyTier::Medium,
+            51..=75 => DifficultyTier::Hard,
+            _ => DifficultyTier::Insane,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FilterCriteria {
     None,
     UserNotOwns,
     RootNotOwns,
     UserAndRootNotOwns,
+    ByCategory(String),
+}
+
+// Filters as they were before a change, restored together by undo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterHistoryEntry {
+    pub criteria: FilterCriteria,
+    pub release_month: Option<(i32, u32)>,
+    pub region: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -71,50 +209,253 @@ pub enum SortCriteria {
     UserOwns,
     RootOwns,
     Name,
+    Category,
+}
+
+impl SortCriteria {
+    pub const ALL: [SortCriteria; 5] = [
+        SortCriteria::Difficulty,
+        SortCriteria::UserOwns,
+        SortCriteria::RootOwns,
+        SortCriteria::Name,
+        SortCriteria::Category,
+    ];
+
+    pub fn compare(self, a: &MachineSummary, b: &MachineSummary) -> Ordering {
+        match self {
+            SortCriteria::Difficulty => a.difficulty.cmp(&b.difficulty), // Ascending
+            SortCriteria::UserOwns => b.user_owns_count.cmp(&a.user_owns_count), // Descending
+            SortCriteria::RootOwns => b.root_owns_count.cmp(&a.root_owns_count),
+            SortCriteria::Name => a.name.cmp(&b.name),
+            SortCriteria::Category => a.category.is_none().cmp(&b.category.is_none()) // Uncategorized last
+                .then_with(|| a.category.cmp(&b.category)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListDensity {
+    Compact,
+    Normal,
+    Expanded,
+}
+
+impl ListDensity {
+    pub fn lines_per_item(self) -> usize {
+        match self {
+            ListDensity::Compact | ListDensity::Normal => 1,
+            ListDensity::Expanded => 3,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputMode {
     Normal,
     Flag,
+    Confirm,
+    Command,
+    Search,
+    Calendar,
+    ClipboardHistory,
+    SecondarySort,
+    Achievements,
+    ActivityHeatmap,
+    WriteupUrl,
+    Metrics,
+}
+
+// Which history Ctrl+Z undoes next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UndoTarget {
+    Filter,
+    Sort,
+}
+
+// Which flag of machine is being submitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagType {
+    User,
+    Root,
+}
+
+impl FlagType {
+    // User flag comes first, None when both are already owned
+    pub fn for_machine(machine: &MachineSummary) -> Option<FlagType> {
+        match (machine.auth_user_in_user_owns, machine.auth_user_in_root_owns) {
+            (false, _) => Some(FlagType::User),
+            (true, false) => Some(FlagType::Root),
+            (true, true) => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FlagType::User => "user",
+            FlagType::Root => "root",
+        }
+    }
+}
+
+// Whether flag character fits HTB{[A-Za-z0-9_!@#$%^&*]+} format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharStatus {
+    Valid,
+    Invalid,
+}
+
+// Action waiting for y/n answer in confirm mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAction {
+    SpawnMachine(u64),
+    DownloadVpnConfig,
+    LinkWriteup(u64),
 }
 
 pub struct App {
     pub running: bool,
-    pub htb_api_key: String, // Hackthebox application key
-    pub client: Client, // Reqwest client
+    pub session_start: Instant,
+    pub config: Config,
+    pub current_theme: Theme,
+    pub htb_client: HTBClient, // Reqwest client with Hackthebox application key
+    pub user_id: Option<u64>, // Owner of application key, looked up at startup
+    pub user_id_error: Option<String>,
+    pub api_reachable: bool, // Result of last background reachability check
+    pub last_reachability_check: Option<Instant>,
+    pub pending_actions: VecDeque<(Instant, Event)>, // Actions queued while offline, oldest first
+    pub user_stats: Option<UserStats>,
+    pub achievements: Vec<UserAchievement>, // Most recent first
+    pub achievements_state: ListState,
+    pub points_history: Vec<PointsEntry>, // Accepted flags across sessions, persisted in config dir
+    pub solve_trends: HashMap<u64, Vec<u64>>, // Weekly solve counts, oldest first, only with snapshot history
+    owns_snapshots: HashMap<u64, Vec<OwnsSnapshot>>, // User owns counts across sessions, persisted in config dir
+    first_difficulties: HashMap<u64, u64>, // Persisted in config dir
+    pub session_points: u64,
+    pub my_writeup_urls: HashMap<u64, String>, // Persisted in config dir
+    pub writeup_input: String,
+    pub writeup_machine_id: Option<u64>, // Machine writeup URL is typed for
 
-    pub machines: Vec<Machine>,
+    pub machines: Vec<MachineSummary>,
+    machines_index: HashMap<u64, usize>, // Machine id to position in machines
+    fetched_machine_ids: HashSet<u64>, // Machines seen in current fetch
+    machines_fetch_generation: u64, // Id of latest fetch, results of older ones are dropped
+    pub machine_details_cache: HashMap<u64, MachineDetail>, // Profiles fetched on demand
+    detail_fetches_in_flight: HashSet<u64>,
+    pending_detail_fetch: Option<(u64, Instant)>, // Selected machine waiting for DETAIL_FETCH_DELAY
+    pub hosts: HashMap<IpAddr, Vec<String>>, // Hostnames from /etc/hosts
+    pub spawn_time: HashMap<u64, Instant>, // Machines spawned during this session
     pub state: ListState,
+    pub tooltip_machine: Option<(MachineSummary, Instant)>, // Quick stats of newly selected machine
+    pub selected_machines: HashSet<u64>, // Machines tagged with Space for bulk actions
+    pub bookmarks: HashSet<u64>, // Persisted in config dir
+    pub wishlist: HashSet<u64>, // Persisted in config dir
     pub info_message: String, // Message for user
     pub filter_criteria: FilterCriteria, // Criteria for filtering
+    pub category_filter: Option<String>, // Category picked with category key
+    pub search_query: String, // Case-insensitive machine name search
+    pub release_month_filter: Option<(i32, u32)>, // (year, month) picked in release calendar
+    pub region_filter: Option<String>, // Server region picked with Ctrl+R
+    pub calendar_month: (i32, u32), // Month shown in release calendar
     pub sort_criteria: SortCriteria, // Criteria for sorting
+    pub secondary_sort_criteria: SortCriteria, // Orders machines with equal primary sort key
+    pub secondary_sort_state: ListState,
+    pub list_density: ListDensity, // Lines per machine in list
+    pub list_offset: usize, // First machine rendered in list, kept up to date by ui
+    pub fullscreen_detail: bool, // Show only details of highlighted machine
+    pub detail_scroll: u16, // Lines scrolled in fullscreen detail
+    pub filter_history: Vec<FilterHistoryEntry>, // Previous filters, most recent last
+    pub sort_history: Vec<SortCriteria>, // Previous sorts, most recent last
+    undo_order: Vec<UndoTarget>,
     
     pub input_mode: InputMode, // input mode
     pub flag_input: String,
+    pub flag_type: Option<FlagType>, // Detected from own status when entering flag mode
+    pub last_flag_submission: Option<Instant>, // Guards against double submission
     pub show_input_field: bool, // control input visibility
     pub selected_machine_ip: Option<String>, // IP of active machine
     pub selected_machine_id: Option<u64>,
+    pub last_ping: Option<Instant>,
+    pub ping_result: Option<(String, Result<f64, String>)>, // Latency in ms for IP
+    pub confirm_action: Option<ConfirmAction>, // Action executed when user answers y
+    pub confirm_message: String,
+    pub clipboard_history: VecDeque<(String, Instant)>, // Most recent copy first
+    pub clipboard_state: ListState,
+    pub command_input: String, // Text typed after ':'
+    pub command_error: Option<String>, // Shown in status bar until next key
     pub event_sender: UnboundedSender<Event>,
 }
 
 impl App {
     // Create new application and accept Hackthebox application key
-    pub fn new(htb_api_key: String, event_sender: UnboundedSender<Event>) ->Self {
+    pub fn new(htb_api_key: String, config: Config, event_sender: UnboundedSender<Event>) ->Self {
         Self {
             running: true,
-            htb_api_key,
-            client: reqwest::Client::new(),
+            session_start: Instant::now(),
+            htb_client: HTBClient::new(htb_api_key, &config),
+            current_theme: config.theme,
+            user_id: None,
+            user_id_error: None,
+            api_reachable: true,
+            last_reachability_check: None,
+            pending_actions: VecDeque::new(),
+            user_stats: None,
+            achievements: Vec::new(),
+            achievements_state: ListState::default(),
+            points_history: load_points_history(),
+            solve_trends: HashMap::new(),
+            owns_snapshots: load_owns_snapshots(),
+            first_difficulties: load_first_difficulties(),
+            session_points: 0,
+            my_writeup_urls: load_writeup_urls(),
+            writeup_input: String::new(),
+            writeup_machine_id: None,
+            config,
             machines: Vec::new(),
+            machines_index: HashMap::new(),
+            fetched_machine_ids: HashSet::new(),
+            machines_fetch_generation: 0,
+            machine_details_cache: HashMap::new(),
+            detail_fetches_in_flight: HashSet::new(),
+            pending_detail_fetch: None,
+            hosts: parse_hosts_file(Path::new("/etc/hosts")),
+            spawn_time: HashMap::new(),
             state: ListState::default(),
+            tooltip_machine: None,
+            selected_machines: HashSet::new(),
+            bookmarks: load_bookmarks(),
+            wishlist: load_wishlist(),
             info_message: String::new(),            
             filter_criteria: FilterCriteria::None,
+            category_filter: None,
+            search_query: String::new(),
+            release_month_filter: None,
+            region_filter: None,
+            calendar_month: (1970, 1),
             sort_criteria: SortCriteria::Difficulty,
+            secondary_sort_criteria: SortCriteria::Name,
+            secondary_sort_state: ListState::default(),
+            list_density: ListDensity::Normal,
+            list_offset: 0,
+            fullscreen_detail: false,
+            detail_scroll: 0,
+            filter_history: Vec::new(),
+            sort_history: Vec::new(),
+            undo_order: Vec::new(),
             input_mode: InputMode::Normal,
             flag_input: String::new(),
+            flag_type: None,
+            last_flag_submission: None,
             show_input_field: false,
             selected_machine_ip: None,
             selected_machine_id: None,
+            last_ping: None,
+            ping_result: None,
+            confirm_action: None,
+            confirm_message: String::new(),
+            clipboard_history: VecDeque::new(),
+            clipboard_state: ListState::default(),
+            command_input: String::new(),
+            command_error: None,
             event_sender,
         }
     }
@@ -123,13 +464,96 @@ impl App {
         self.running = false;
     }
 
+    pub fn tick(&mut self) {
+        if self.config.auto_ping {
+            self.request_ping_machine();
+        }
+        self.request_check_reachability();
+        self.expire_pending_actions();
+        if let Some((machine_id, selected_at)) = self.pending_detail_fetch {
+            if selected_at.elapsed() >= DETAIL_FETCH_DELAY {
+                self.pending_detail_fetch = None;
+                self.request_machine_detail(machine_id);
+            }
+        }
+    }
+
+    // Skips machines already cached or being fetched
+    fn request_machine_detail(&mut self, machine_id: u64) {
+        if !self.machine_details_cache.contains_key(&machine_id) && self.detail_fetches_in_flight.insert(machine_id) {
+            self.event_sender
+                .send(Event::FetchMachineDetail(machine_id))
+                .expect("Failed to send FetchMachineDetail event");
+        }
+    }
+
+    fn request_check_reachability(&mut self) {
+        if self.last_reachability_check.is_none_or(|last| last.elapsed() >= REACHABILITY_CHECK_INTERVAL) {
+            self.last_reachability_check = Some(Instant::now());
+            self.event_sender
+                .send(Event::CheckReachability)
+                .expect("Failed to send CheckReachability event");
+        }
+    }
+
+    pub fn handle_check_reachability_result(&mut self, reachable: bool) {
+        let restored = reachable && !self.api_reachable;
+        self.api_reachable = reachable;
+        if restored {
+            let count = self.pending_actions.len();
+            for (_, event) in self.pending_actions.drain(..) {
+                self.event_sender
+                    .send(event)
+                    .expect("Failed to send queued event");
+            }
+            if count > 0 {
+                self.info_message = format!("Connection restored, replaying {} queued action(s)", count);
+            }
+        }
+    }
+
+    // Send action now, or keep it for replay while HTB API is unreachable
+    fn send_or_queue(&mut self, event: Event) {
+        if self.api_reachable {
+            self.event_sender
+                .send(event)
+                .expect("Failed to send action event");
+        } else {
+            self.pending_actions.push_back((Instant::now(), event));
+            self.info_message = "HTB API unreachable, action queued until connection restores".to_string();
+        }
+    }
+
+    fn expire_pending_actions(&mut self) {
+        let before = self.pending_actions.len();
+        self.pending_actions.retain(|(queued_at, _)| queued_at.elapsed() < PENDING_ACTION_EXPIRY);
+        let expired = before - self.pending_actions.len();
+        if expired > 0 {
+            self.info_message = format!("Discarded {} queued action(s) older than 5 minutes", expired);
+        }
+    }
+
+    fn request_ping_machine(&mut self) {
+        let due = self.last_ping.is_none_or(|last_ping| last_ping.elapsed() >= PING_INTERVAL);
+        if let (true, Some(ip)) = (due, self.selected_machine_ip.clone()) {
+            self.last_ping = Some(Instant::now());
+            self.event_sender
+                .send(Event::PingMachine(ip))
+                .expect("Failed to send PingMachine event");
+        }
+    }
+
+    pub fn handle_ping_machine_result(&mut self, ip: String, result: Result<f64, String>) {
+        self.ping_result = Some((ip, result));
+    }
+
     pub fn next(&mut self) {
         let filtered = self.filtered_machines(); // Get filtered list
         let sorted = self.sorted_machines(filtered); // Get sorted list
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= sorted.len().saturating_sub(1) { // Saturating sub prevents underflowing
-                    0
+                    if self.config.wrap_navigation { 0 } else { sorted.len().saturating_sub(1) }
                 } else {
                     i + 1
                 }
@@ -146,7 +570,7 @@ impl App {
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    sorted.len().saturating_sub(1) // Saturating sub prevents underflowing
+                    if self.config.wrap_navigation { sorted.len().saturating_sub(1) } else { 0 } // Saturating sub prevents underflowing
                 } else {
                     i - 1
                 }
@@ -157,25 +581,285 @@ impl App {
         self.update_input_fields();
     }
 
+    // Tag or untag highlighted machine
+    pub fn toggle_machine_selection(&mut self) {
+        if let Some(selected) = self.state.selected() {
+            let filtered = self.filtered_machines();
+            let sorted = self.sorted_machines(filtered);
+            if let Some(machine) = sorted.get(selected) {
+                if !self.selected_machines.remove(&machine.id) {
+                    self.selected_machines.insert(machine.id);
+                }
+            }
+        }
+    }
+
+    pub fn toggle_fullscreen_detail(&mut self) {
+        if self.fullscreen_detail || self.highlighted_machine().is_some() {
+            self.fullscreen_detail = !self.fullscreen_detail;
+            self.detail_scroll = 0;
+        }
+        // Detail view needs profile right away
+        if let Some(machine) = self.highlighted_machine().filter(|_| self.fullscreen_detail) {
+            self.pending_detail_fetch = None;
+            self.request_machine_detail(machine.id);
+        }
+    }
+
+    pub fn scroll_detail(&mut self, offset: i16) {
+        self.detail_scroll = self.detail_scroll.saturating_add_signed(offset);
+    }
+
+    pub fn clear_machine_selection(&mut self) {
+        self.selected_machines.clear();
+    }
+
+    // Tagged machines, or highlighted one when nothing is tagged
+    fn batch_machine_ids(&self) -> Vec<u64> {
+        if self.selected_machines.is_empty() {
+            self.highlighted_machine().map(|m| m.id).into_iter().collect()
+        } else {
+            self.selected_machines.iter().copied().collect()
+        }
+    }
+
+    // Add tagged machines to bookmarks, or remove them when all are already bookmarked
+    pub fn bookmark_selected_machines(&mut self) {
+        let ids = self.batch_machine_ids();
+        let removed = toggle_all(&mut self.bookmarks, &ids);
+        self.info_message = match save_bookmarks(&self.bookmarks) {
+            Ok(()) if removed => format!("Removed {} machine(s) from bookmarks", ids.len()),
+            Ok(()) => format!("Bookmarked {} machine(s)", ids.len()),
+            Err(e) => format!("Error saving bookmarks: {}", e),
+        };
+    }
+
+    // Add tagged machines to wishlist, or remove them when all are already wishlisted
+    pub fn wishlist_selected_machines(&mut self) {
+        let ids = self.batch_machine_ids();
+        let removed = toggle_all(&mut self.wishlist, &ids);
+        self.info_message = match save_wishlist(&self.wishlist) {
+            Ok(()) if removed => format!("Removed {} machine(s) from wishlist", ids.len()),
+            Ok(()) => format!("Wishlisted {} machine(s)", ids.len()),
+            Err(e) => format!("Error saving wishlist: {}", e),
+        };
+    }
+
+    // Command bar prefilled with export, which writes only tagged machines when any are tagged
+    pub fn enter_export_mode(&mut self) {
+        self.enter_command_mode();
+        self.command_input = "export json ".to_string();
+    }
+
+    pub fn request_fetch_user_id(&self) {
+        self.event_sender
+            .send(Event::FetchUserId)
+            .expect("Failed to send FetchUserId event");
+    }
+
+    pub fn request_fetch_user_stats(&self) {
+        self.event_sender
+            .send(Event::FetchUserStats)
+            .expect("Failed to send FetchUserStats event");
+    }
+
+    pub fn handle_fetch_user_id_result(&mut self, result: Result<u64, String>) {
+        match result {
+            Ok(user_id) => {
+                self.user_id = Some(user_id);
+                self.user_id_error = None;
+                self.request_fetch_user_stats();
+            }
+            Err(e) => {
+                self.user_id = None;
+                self.user_id_error = Some(e);
+            }
+        }
+    }
+
+    pub fn handle_fetch_user_stats_result(&mut self, result: Result<UserStats, String>) {
+        match result {
+            Ok(stats) => {
+                self.user_stats = Some(stats);
+            }
+            Err(e) => {
+                self.info_message = format!("Error fetching user stats: {}", e);
+            }
+        }
+    }
+
+    pub fn handle_fetch_achievements_result(&mut self, result: Result<Vec<UserAchievement>, String>) {
+        match result {
+            Ok(achievements) => {
+                self.achievements = achievements;
+                // Refresh may arrive while popup is open
+                if self.input_mode == InputMode::Achievements {
+                    if self.achievements.is_empty() {
+                        self.achievements_state.select(None);
+                        self.input_mode = InputMode::Normal;
+                    } else if self.achievements_state.selected().is_some_and(|i| i >= self.achievements.len()) {
+                        self.achievements_state.select(Some(self.achievements.len() - 1));
+                    }
+                }
+            }
+            Err(e) => {
+                self.info_message = format!("Error fetching achievements: {}", e);
+            }
+        }
+    }
+
+    pub fn enter_achievements_mode(&mut self) {
+        if self.achievements.is_empty() {
+            self.info_message = "No achievements loaded".to_string();
+            return;
+        }
+        self.achievements_state.select(Some(0));
+        self.input_mode = InputMode::Achievements;
+    }
+
+    pub fn next_achievement(&mut self) {
+        let len = self.achievements.len();
+        if len == 0 {
+            return;
+        }
+        let i = self.achievements_state.selected().map_or(0, |i| (i + 1) % len);
+        self.achievements_state.select(Some(i));
+    }
+
+    pub fn previous_achievement(&mut self) {
+        let len = self.achievements.len();
+        if len == 0 {
+            return;
+        }
+        let i = self.achievements_state.selected().map_or(0, |i| (i + len - 1) % len);
+        self.achievements_state.select(Some(i));
+    }
+
     pub fn request_fetch_machines(&self) {
         self.event_sender
             .send(Event::FetchMachines)
             .expect("Failed to send FetchMachines event");
     }
 
-    pub fn handle_fetch_machines_result(&mut self, result: Result<(Vec<Machine>, Result<(), String>), String>) {
+    // New fetch supersedes any still in flight, returns its generation id
+    pub fn start_machines_fetch(&mut self) -> u64 {
+        self.machines_fetch_generation += 1;
+        self.fetched_machine_ids.clear();
+        self.machines_fetch_generation
+    }
+
+    // One page of machines, more may follow until FetchMachinesDone
+    pub fn handle_fetch_machines_result(&mut self, generation: u64, machines: Vec<MachineSummary>) {
+        if generation != self.machines_fetch_generation {
+            return;
+        }
+        let selected_id = self.highlighted_machine().map(|m| m.id);
+        self.merge_machines(machines);
+        self.reselect_machine(selected_id);
+    }
+
+    pub fn handle_fetch_machines_done(&mut self, generation: u64, result: Result<(), String>) {
+        if generation != self.machines_fetch_generation {
+            return;
+        }
+        let selected_id = self.highlighted_machine().map(|m| m.id);
         match result {
-            Ok((machines, completion_result)) => {
-                self.machines.extend(machines);
-                self.update_input_fields();
-                if let Err(completion_error) = completion_result {
-                        self.info_message = format!("Error fetchin machines: {}", completion_error);
+            Ok(()) => {
+                // Only drop machines missing from a complete listing
+                let fetched_ids = &self.fetched_machine_ids;
+                self.machines.retain(|m| fetched_ids.contains(&m.id));
+                self.rebuild_machines_index();
+                self.record_owns_snapshots();
+                if let Err(e) = save_first_difficulties(&self.first_difficulties) {
+                    self.info_message = format!("Error saving machine difficulties: {}", e);
                 }
             }
             Err(e) => {
                 self.info_message = format!("Error fetching machines: {}", e);
             }
         }
+        self.fetched_machine_ids.clear();
+        self.reselect_machine(selected_id);
+    }
+
+    // Update known machines in place and append new ones so refresh keeps list order
+    fn merge_machines(&mut self, machines: Vec<MachineSummary>) {
+        for mut machine in machines {
+            self.fetched_machine_ids.insert(machine.id);
+            let first_difficulty = *self.first_difficulties.entry(machine.id).or_insert(machine.difficulty);
+            machine.original_difficulty = machine.original_difficulty.or(Some(first_difficulty));
+            match self.machines_index.get(&machine.id) {
+                Some(&index) => {
+                    let existing = &mut self.machines[index];
+                    if machine.category.is_none() {
+                        machine.category = existing.category.take();
+                    }
+                    if machine.server_region.is_none() {
+                        machine.server_region = existing.server_region.take();
+                    }
+                    *existing = machine;
+                }
+                None => {
+                    self.machines_index.insert(machine.id, self.machines.len());
+                    self.machines.push(machine);
+                }
+            }
+        }
+    }
+
+    // Snapshot owns counts after complete listing so solve trends build up across sessions
+    fn record_owns_snapshots(&mut self) {
+        let timestamp = unix_now();
+        for machine in &self.machines {
+            let snapshot = OwnsSnapshot { timestamp, user_owns_count: machine.user_owns_count };
+            record_owns_snapshot(self.owns_snapshots.entry(machine.id).or_default(), snapshot, SOLVE_TREND_WEEKS as u64 + 1);
+        }
+        if let Err(e) = save_owns_snapshots(&self.owns_snapshots) {
+            self.info_message = format!("Error saving owns snapshots: {}", e);
+        }
+        self.solve_trends.clear();
+        if let Some((machine, _)) = &self.tooltip_machine {
+            self.fetch_machine_trend(machine.id);
+        }
+    }
+
+    // Solves per week for last 8 weeks from stored snapshots, none without history
+    pub fn fetch_machine_trend(&mut self, machine_id: u64) {
+        let trend = self.owns_snapshots
+            .get(&machine_id)
+            .and_then(|snapshots| weekly_deltas(snapshots, unix_now(), SOLVE_TREND_WEEKS));
+        match trend {
+            Some(trend) => {
+                self.solve_trends.insert(machine_id, trend);
+            }
+            None => {
+                self.solve_trends.remove(&machine_id);
+            }
+        }
+    }
+
+    fn rebuild_machines_index(&mut self) {
+        self.machines_index = self.machines
+            .iter()
+            .enumerate()
+            .map(|(index, machine)| (machine.id, index))
+            .collect();
+    }
+
+    pub fn machine_by_id(&self, id: u64) -> Option<&MachineSummary> {
+        self.machines_index.get(&id).and_then(|&index| self.machines.get(index))
+    }
+
+    fn machine_by_id_mut(&mut self, id: u64) -> Option<&mut MachineSummary> {
+        self.machines_index.get(&id).and_then(|&index| self.machines.get_mut(index))
+    }
+
+    fn reselect_machine(&mut self, machine_id: Option<u64>) {
+        if let Some(machine_id) = machine_id {
+            let sorted = self.sorted_machines(self.filtered_machines());
+            self.state.select(sorted.iter().position(|m| m.id == machine_id));
+        }
+        self.update_input_fields();
     }
 
     pub fn request_spawn_machine(&mut self) {
@@ -184,32 +868,108 @@ impl App {
             let sorted_machines = self.sorted_machines(filtered_machines);
             if let Some(machine) = sorted_machines.get(selected) {
                 let machine_id = machine.id;
-                
-                // Deactivate currently active machine
-                if let Some(previous_active_id) = self.selected_machine_id {
-                    if let Some(previous_machine) = self.machines.iter_mut().find(|m| m.id == previous_active_id) {
-                        previous_machine.active = Value::Bool(false);
-                        previous_machine.ip = None;
-                    }
+
+                // Warn before hitting the subscription limit of simultaneous instances
+                let active_count = self.machines.iter().filter(|m| m.is_active()).count();
+                if active_count >= self.config.max_active_machines as usize {
+                    self.request_confirm(
+                        ConfirmAction::SpawnMachine(machine_id),
+                        format!(
+                            "You already have {} active machine(s). Spawning may fail or terminate another. Continue? (y/n)",
+                            active_count
+                        ),
+                    );
+                    return;
                 }
 
-                self.event_sender
-                    .send(Event::SpawnMachine(machine_id))
-                    .expect("Failed to send SpawnMachine event");
+                self.send_spawn_machine(machine_id);
+            }
+        }
+        self.update_input_fields();
+    }
+
+    fn send_spawn_machine(&mut self, machine_id: u64) {
+        // Deactivate currently active machine
+        if let Some(previous_active_id) = self.selected_machine_id {
+            if let Some(previous_machine) = self.machine_by_id_mut(previous_active_id) {
+                previous_machine.active = Value::Bool(false);
+                previous_machine.ip = None;
             }
         }
+
+        self.send_or_queue(Event::SpawnMachine(machine_id));
         self.update_input_fields();
     }
 
+    pub fn request_confirm(&mut self, action: ConfirmAction, message: String) {
+        self.confirm_action = Some(action);
+        self.confirm_message = message;
+        self.input_mode = InputMode::Confirm;
+    }
+
+    pub fn accept_confirm(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.confirm_message.clear();
+        if let Some(action) = self.confirm_action.take() {
+            match action {
+                ConfirmAction::SpawnMachine(machine_id) => self.send_spawn_machine(machine_id),
+                ConfirmAction::DownloadVpnConfig => {
+                    self.event_sender
+                        .send(Event::DownloadVpnConfig)
+                        .expect("Failed to send DownloadVpnConfig event");
+                }
+                ConfirmAction::LinkWriteup(machine_id) => {
+                    self.writeup_machine_id = Some(machine_id);
+                    self.writeup_input = self.my_writeup_urls.get(&machine_id).cloned().unwrap_or_default();
+                    self.input_mode = InputMode::WriteupUrl;
+                }
+            }
+        }
+    }
+
+    pub fn reject_confirm(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.confirm_message.clear();
+        self.confirm_action = None;
+        self.info_message = "Action cancelled".to_string();
+    }
+
+    pub fn handle_fetch_machine_detail_result(&mut self, machine_id: u64, result: Result<Box<MachineDetail>, String>) {
+        self.detail_fetches_in_flight.remove(&machine_id);
+        match result {
+            Ok(detail) => {
+                let server_region = detail.server_region.clone();
+                let category = detail.category.clone();
+                self.machine_details_cache.insert(machine_id, *detail);
+
+                // Region or category filter may now match, keep highlighted machine selected
+                let selected_id = self.highlighted_machine().map(|m| m.id);
+                if let Some(machine) = self.machine_by_id_mut(machine_id) {
+                    if server_region.is_some() {
+                        machine.server_region = server_region;
+                    }
+                    if category.is_some() {
+                        machine.category = category;
+                    }
+                }
+                self.reselect_machine(selected_id);
+            }
+            Err(e) => {
+                self.info_message = format!("Error fetching machine details: {}", e);
+            }
+        }
+    }
+
     pub fn handle_spawn_machine_result(&mut self, result: Result<(u64, String), String>) {
         match result {
             Ok((machine_id, ip)) => {
-                if let Some(machine) = self.machines.iter_mut().find(|m| m.id == machine_id) {
+                self.spawn_time.insert(machine_id, Instant::now());
+                if let Some(machine) = self.machine_by_id_mut(machine_id) {
                     machine.active = Value::Bool(true);
-                    machine.ip = Some(ip);
+                    machine.ip = Some(ip.clone());
                     self.info_message = format!("Machine {} spawned successfully", machine_id);
-                    self.selected_machine_ip = machine.ip.clone();
-                    self.selected_machine_id = Some(machine.id);
+                    self.selected_machine_ip = Some(ip);
+                    self.selected_machine_id = Some(machine_id);
                 } else {
                     self.info_message = format!("Machine {} spawned, but not found in machine list", machine_id);
                 }
@@ -221,81 +981,429 @@ impl App {
         }
     }
 
-    pub fn request_submit_flag(&self) {
-        if let (Some(machine_id), flag) = (self.selected_machine_id, self.flag_input.clone()) {
-            self.event_sender
-                .send(Event::SubmitFlag(machine_id, flag))
-                .expect("Failed to send SubmitFlag event");
+    pub fn request_terminate_machine(&mut self) {
+        match self.highlighted_machine() {
+            Some(machine) if machine.is_active() => {
+                self.event_sender
+                    .send(Event::TerminateMachine(machine.id))
+                    .expect("Failed to send TerminateMachine event");
+            }
+            _ => {
+                self.info_message = "Selected machine is not active".to_string();
+            }
         }
     }
 
-    pub fn handle_submit_flag_result(&mut self, result: Result<String, String>) {
+    pub fn handle_terminate_machine_result(&mut self, result: Result<u64, String>) {
         match result {
-            Ok(message) => {
-                self.info_message = message;
+            Ok(machine_id) => {
+                self.spawn_time.remove(&machine_id);
+                if let Some(machine) = self.machine_by_id_mut(machine_id) {
+                    machine.active = Value::Bool(false);
+                    machine.ip = None;
+                }
+                if self.selected_machine_id == Some(machine_id) {
+                    self.selected_machine_id = None;
+                }
+                self.info_message = format!("Machine {} terminated", machine_id);
+                self.update_input_fields();
             }
             Err(e) => {
-                self.info_message = format!("Error sending flag: {}", e);
+                self.info_message = format!("Error terminating machine: {}", e);
             }
         }
     }
 
-    pub fn filtered_machines(&self) -> Vec<Machine> {
-        let mut filtered = self.machines.clone();
-        filtered.retain(|machine| { // Remove all elements that do not met criteria
-            match self.filter_criteria {
-                FilterCriteria::None => true,
-                FilterCriteria::UserNotOwns => !machine.auth_user_in_user_owns,
-                FilterCriteria::RootNotOwns => !machine.auth_user_in_root_owns,
-                FilterCriteria::UserAndRootNotOwns => !machine.auth_user_in_user_owns && !machine.auth_user_in_root_owns,
+    pub fn request_submit_flag(&mut self) {
+        if let Some(last_submission) = self.last_flag_submission {
+            if last_submission.elapsed() < FLAG_SUBMISSION_COOLDOWN {
+                self.info_message = "Please wait before resubmitting".to_string();
+                return;
             }
-        });
-        filtered
+        }
+
+        if let (Some(machine_id), flag) = (self.selected_machine_id, self.flag_input.clone()) {
+            self.last_flag_submission = Some(Instant::now());
+            self.send_or_queue(Event::SubmitFlag(machine_id, flag, self.flag_type));
+        }
     }
 
-    pub fn sorted_machines(&self, machines: Vec<Machine>) -> Vec<Machine> {
+    pub fn handle_submit_flag_result(&mut self, result: Result<(u64, Option<FlagType>, String), String>) {
+        match result {
+            Ok((machine_id, flag_type, message)) => {
+                self.info_message = message;
+                self.record_points(machine_id, flag_type);
+                self.mark_flag_owned(machine_id, flag_type);
+                // Next submission in same flag session is for remaining flag
+                self.flag_input.clear();
+                self.flag_type = self.machine_by_id(machine_id).and_then(FlagType::for_machine);
+                if self.flag_type.is_none() && self.input_mode == InputMode::Flag {
+                    self.input_mode = InputMode::Normal;
+                }
+                self.update_input_fields();
+                // Root flag on top of owned user flag completes machine
+                let fully_owned = self.machine_by_id(machine_id)
+                    .is_some_and(|m| m.auth_user_in_user_owns && m.auth_user_in_root_owns);
+                if flag_type == Some(FlagType::Root) && fully_owned {
+                    self.request_confirm(
+                        ConfirmAction::LinkWriteup(machine_id),
+                        "🎉 Machine owned! Want to link your writeup? (y/n)".to_string(),
+                    );
+                }
+            }
+            Err(e) => {
+                self.info_message = format!("Error sending flag: {}", e);
+            }
+        }
+    }
+
+    // Reflect accepted flag before list refresh arrives
+    fn mark_flag_owned(&mut self, machine_id: u64, flag_type: Option<FlagType>) {
+        if let Some(machine) = self.machine_by_id_mut(machine_id) {
+            match flag_type {
+                Some(FlagType::User) => machine.auth_user_in_user_owns = true,
+                Some(FlagType::Root) => machine.auth_user_in_root_owns = true,
+                None => {}
+            }
+        }
+    }
+
+    pub fn cancel_flag_input(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.flag_input.clear();
+        self.flag_type = None;
+    }
+
+    fn record_points(&mut self, machine_id: u64, flag_type: Option<FlagType>) {
+        let Some(machine) = self.machine_by_id(machine_id) else {
+            return;
+        };
+        let entry = PointsEntry {
+            timestamp: unix_now(),
+            machine_id,
+            points: machine.points,
+            own_type: flag_type.map_or("unknown", FlagType::as_str).to_string(),
+        };
+        self.session_points += entry.points;
+        self.points_history.push(entry);
+        if let Err(e) = save_points_history(&self.points_history) {
+            self.info_message = format!("Error saving points history: {}", e);
+        }
+    }
+
+    pub fn cancel_writeup_input(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.writeup_input.clear();
+        self.writeup_machine_id = None;
+    }
+
+    pub fn request_submit_writeup(&mut self) {
+        let url = self.writeup_input.trim().to_string();
+        if url.is_empty() {
+            self.info_message = "Writeup URL is empty".to_string();
+            return;
+        }
+        if let Some(machine_id) = self.writeup_machine_id {
+            self.event_sender
+                .send(Event::SubmitWriteup(machine_id, url))
+                .expect("Failed to send SubmitWriteup event");
+        }
+        self.cancel_writeup_input();
+    }
+
+    pub fn handle_submit_writeup_result(&mut self, result: Result<(u64, String), String>) {
+        match result {
+            Ok((machine_id, url)) => {
+                self.my_writeup_urls.insert(machine_id, url);
+                self.info_message = match save_writeup_urls(&self.my_writeup_urls) {
+                    Ok(()) => format!("Writeup linked for machine {}", machine_id),
+                    Err(e) => format!("Writeup linked, but saving it failed: {}", e),
+                };
+            }
+            Err(e) => {
+                self.info_message = format!("Error submitting writeup: {}", e);
+            }
+        }
+    }
+
+    pub fn all_time_points(&self) -> u64 {
+        self.points_history.iter().map(|entry| entry.points).sum()
+    }
+
+    pub fn request_download_vpn_config(&mut self) {
+        let message = format!(
+            "Download VPN config to {}? (y/n)",
+            self.config.vpn_config_path.display()
+        );
+        self.request_confirm(ConfirmAction::DownloadVpnConfig, message);
+    }
+
+    pub fn handle_download_vpn_config_result(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                self.info_message = format!("VPN config saved to {}", self.config.vpn_config_path.display());
+            }
+            Err(e) => {
+                self.info_message = format!("Error downloading VPN config: {}", e);
+            }
+        }
+    }
+
+    // Share of user and root flags owned, optionally limited to retired or active machines
+    pub fn completion_ratio(&self, retired: Option<bool>) -> f64 {
+        let machines: Vec<&MachineSummary> = self.machines
+            .iter()
+            .filter(|m| retired.is_none_or(|retired| m.retired == retired))
+            .collect();
+        if machines.is_empty() {
+            return 0.0;
+        }
+
+        let owned = machines
+            .iter()
+            .map(|m| m.auth_user_in_user_owns as usize + m.auth_user_in_root_owns as usize)
+            .sum::<usize>();
+        owned as f64 / (2 * machines.len()) as f64
+    }
+
+    // Machine counts as (linux, windows, other)
+    pub fn os_distribution(&self) -> (usize, usize, usize) {
+        self.machines.iter().fold((0, 0, 0), |(linux, windows, other), m| {
+            match m.os.to_lowercase().as_str() {
+                "linux" => (linux + 1, windows, other),
+                "windows" => (linux, windows + 1, other),
+                _ => (linux, windows, other + 1),
+            }
+        })
+    }
+
+    pub fn filtered_machines(&self) -> Vec<MachineSummary> {
+        let mut filtered = self.machines.clone();
+        filtered.retain(|machine| { // Remove all elements that do not met criteria
+            match &self.filter_criteria {
+                FilterCriteria::None => true,
+                FilterCriteria::UserNotOwns => !machine.auth_user_in_user_owns,
+                FilterCriteria::RootNotOwns => !machine.auth_user_in_root_owns,
+                FilterCriteria::UserAndRootNotOwns => !machine.auth_user_in_user_owns && !machine.auth_user_in_root_owns,
+                FilterCriteria::ByCategory(category) => machine.category.as_ref() == Some(category),
+            }
+        });
+        if let Some((year, month)) = self.release_month_filter {
+            filtered.retain(|machine| {
+                machine.release_parsed().is_some_and(|date| date.year == year && date.month == month)
+            });
+        }
+        if let Some(region) = &self.region_filter {
+            filtered.retain(|machine| machine.server_region.as_ref() == Some(region));
+        }
+        if !self.search_query.is_empty() {
+            let query = self.search_query.to_lowercase();
+            filtered.retain(|machine| machine.name.to_lowercase().contains(&query));
+        }
+        filtered
+    }
+
+    pub fn sorted_machines(&self, machines: Vec<MachineSummary>) -> Vec<MachineSummary> {
         let mut sorted = machines;
         sorted.sort_by(|a, b| {
-            match self.sort_criteria {
-                SortCriteria::Difficulty => a.difficulty.cmp(&b.difficulty), // Ascending
-                SortCriteria::UserOwns => b.user_owns_count.cmp(&a.user_owns_count), // Descending
-                SortCriteria::RootOwns => b.root_owns_count.cmp(&a.root_owns_count),
-                SortCriteria::Name => a.name.cmp(&b.name)
-            }
+            self.sort_criteria.compare(a, b)
+                .then_with(|| self.secondary_sort_criteria.compare(a, b)) // Tie breaker
         });
         sorted
     }
 
     pub fn cycle_filter(&mut self) {
+        self.push_filter_history();
         self.filter_criteria = match self.filter_criteria {
             FilterCriteria::None => FilterCriteria::UserNotOwns,
             FilterCriteria::UserNotOwns => FilterCriteria::RootNotOwns,
             FilterCriteria::RootNotOwns => FilterCriteria::UserAndRootNotOwns,
-            FilterCriteria::UserAndRootNotOwns => FilterCriteria::None,
+            FilterCriteria::UserAndRootNotOwns | FilterCriteria::ByCategory(_) => FilterCriteria::None,
+        };
+        self.category_filter = None;
+        if self.release_month_filter.take().is_some() {
+            self.info_message = "Release month filter cleared".to_string();
+        }
+        self.state.select(None);
+        self.update_input_fields();
+    }
+
+    // Cycle through known categories in alphabetical order, then back to no filter
+    pub fn cycle_category_filter(&mut self) {
+        let mut categories: Vec<String> = self.machines
+            .iter()
+            .filter_map(|m| m.category.clone())
+            .collect();
+        categories.sort();
+        categories.dedup();
+
+        let next = match &self.category_filter {
+            Some(current) => categories.iter().skip_while(|c| *c != current).nth(1).cloned(),
+            None => categories.first().cloned(),
+        };
+
+        self.push_filter_history();
+        self.filter_criteria = match &next {
+            Some(category) => FilterCriteria::ByCategory(category.clone()),
+            None => FilterCriteria::None,
+        };
+        self.category_filter = next;
+        self.state.select(None);
+        self.update_input_fields();
+    }
+
+    // Cycle through server regions of loaded machines, then back to all regions
+    pub fn cycle_region_filter(&mut self) {
+        let mut regions: Vec<String> = self.machines
+            .iter()
+            .filter_map(|m| m.server_region.clone())
+            .collect();
+        regions.sort();
+        regions.dedup();
+
+        if regions.is_empty() {
+            self.info_message = "No server regions known for loaded machines".to_string();
+            return;
+        }
+
+        self.push_filter_history();
+        self.region_filter = match &self.region_filter {
+            Some(current) => regions.iter().skip_while(|r| *r != current).nth(1).cloned(),
+            None => regions.first().cloned(),
         };
         self.state.select(None);
         self.update_input_fields();
     }
 
     pub fn cycle_sort(&mut self) {
+        self.push_sort_history();
         self.sort_criteria = match self.sort_criteria {
             SortCriteria::Difficulty => SortCriteria::UserOwns,
             SortCriteria::UserOwns => SortCriteria::RootOwns,
             SortCriteria::RootOwns => SortCriteria::Name,
-            SortCriteria::Name => SortCriteria::Difficulty,
+            SortCriteria::Name => SortCriteria::Category,
+            SortCriteria::Category => SortCriteria::Difficulty,
         };
         self.state.select(None);
         self.update_input_fields();
     }
 
+    fn push_filter_history(&mut self) {
+        self.filter_history.push(FilterHistoryEntry {
+            criteria: self.filter_criteria.clone(),
+            release_month: self.release_month_filter,
+            region: self.region_filter.clone(),
+        });
+        if self.filter_history.len() > UNDO_HISTORY_LIMIT {
+            self.filter_history.remove(0);
+            self.drop_oldest_undo(UndoTarget::Filter);
+        }
+        self.undo_order.push(UndoTarget::Filter);
+    }
+
+    fn push_sort_history(&mut self) {
+        self.sort_history.push(self.sort_criteria);
+        if self.sort_history.len() > UNDO_HISTORY_LIMIT {
+            self.sort_history.remove(0);
+            self.drop_oldest_undo(UndoTarget::Sort);
+        }
+        self.undo_order.push(UndoTarget::Sort);
+    }
+
+    fn drop_oldest_undo(&mut self, target: UndoTarget) {
+        if let Some(index) = self.undo_order.iter().position(|t| *t == target) {
+            self.undo_order.remove(index);
+        }
+    }
+
+    // Undo whichever of filter or sort was changed most recently
+    pub fn undo_last_change(&mut self) {
+        match self.undo_order.last() {
+            Some(UndoTarget::Filter) => self.undo_last_filter(),
+            Some(UndoTarget::Sort) => self.undo_last_sort(),
+            None => self.info_message = "Nothing to undo".to_string(),
+        }
+    }
+
+    pub fn undo_last_filter(&mut self) {
+        if let Some(entry) = self.filter_history.pop() {
+            self.drop_latest_undo(UndoTarget::Filter);
+            self.category_filter = match &entry.criteria {
+                FilterCriteria::ByCategory(category) => Some(category.clone()),
+                _ => None,
+            };
+            self.filter_criteria = entry.criteria;
+            self.release_month_filter = entry.release_month;
+            self.region_filter = entry.region;
+            self.state.select(None);
+            self.update_input_fields();
+        }
+    }
+
+    pub fn undo_last_sort(&mut self) {
+        if let Some(criteria) = self.sort_history.pop() {
+            self.drop_latest_undo(UndoTarget::Sort);
+            self.sort_criteria = criteria;
+            self.state.select(None);
+            self.update_input_fields();
+        }
+    }
+
+    fn drop_latest_undo(&mut self, target: UndoTarget) {
+        if let Some(index) = self.undo_order.iter().rposition(|t| *t == target) {
+            self.undo_order.remove(index);
+        }
+    }
+
+    pub fn enter_secondary_sort_mode(&mut self) {
+        let current = SortCriteria::ALL.iter().position(|c| *c == self.secondary_sort_criteria);
+        self.secondary_sort_state.select(current);
+        self.input_mode = InputMode::SecondarySort;
+    }
+
+    pub fn next_secondary_sort(&mut self) {
+        let i = self.secondary_sort_state.selected().map_or(0, |i| (i + 1) % SortCriteria::ALL.len());
+        self.secondary_sort_state.select(Some(i));
+    }
+
+    pub fn previous_secondary_sort(&mut self) {
+        let len = SortCriteria::ALL.len();
+        let i = self.secondary_sort_state.selected().map_or(0, |i| (i + len - 1) % len);
+        self.secondary_sort_state.select(Some(i));
+    }
+
+    pub fn apply_secondary_sort(&mut self) {
+        if let Some(criteria) = self.secondary_sort_state.selected().and_then(|i| SortCriteria::ALL.get(i)) {
+            self.secondary_sort_criteria = *criteria;
+            self.state.select(None);
+            self.update_input_fields();
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn cycle_list_density(&mut self) {
+        self.list_density = match self.list_density {
+            ListDensity::Compact => ListDensity::Normal,
+            ListDensity::Normal => ListDensity::Expanded,
+            ListDensity::Expanded => ListDensity::Compact,
+        };
+    }
+
     pub fn update_input_fields(&mut self) {
         if let Some(selected) = self.state.selected() {
             let filtered = self.filtered_machines();
             let sorted = self.sorted_machines(filtered);
             if selected < sorted.len() {
                 let machine = &sorted[selected];
-                self.show_input_field = machine.is_active()
-                    && (!machine.auth_user_in_user_owns && !machine.auth_user_in_root_owns);
+                if self.tooltip_machine.as_ref().is_none_or(|(tooltip, _)| tooltip.id != machine.id) {
+                    self.tooltip_machine = Some((machine.clone(), Instant::now()));
+                    // Fetched on tick once selection settles, so scrolling past does not fetch every row
+                    self.pending_detail_fetch = Some((machine.id, Instant::now()));
+                    if !self.solve_trends.contains_key(&machine.id) {
+                        self.fetch_machine_trend(machine.id);
+                    }
+                }
+                self.show_input_field = machine.is_active() && FlagType::for_machine(machine).is_some();
                 self.selected_machine_ip = machine.ip.clone();
             } else {
                 self.show_input_field = false;
@@ -309,57 +1417,369 @@ impl App {
 
     pub fn enter_flag_input_mode(&mut self) {
         if self.show_input_field {
+            self.flag_type = self.selected_machine_id
+                .and_then(|id| self.machine_by_id(id))
+                .and_then(FlagType::for_machine);
             self.input_mode = InputMode::Flag;
         }
     }
 
+    pub fn enter_search_mode(&mut self) {
+        self.input_mode = InputMode::Search;
+    }
+
+    // Start new search from letter typed in normal mode
+    pub fn enter_search_mode_with_char(&mut self, c: char) {
+        self.search_query.clear();
+        self.enter_search_mode();
+        self.push_search_char(c);
+    }
+
+    pub fn push_search_char(&mut self, c: char) {
+        self.search_query.push(c);
+        self.state.select(None);
+        self.update_input_fields();
+    }
+
+    pub fn pop_search_char(&mut self) {
+        self.search_query.pop();
+        self.state.select(None);
+        self.update_input_fields();
+    }
+
+    // Leave search mode and drop query
+    pub fn cancel_search(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.search_query.clear();
+        self.state.select(None);
+        self.update_input_fields();
+    }
+
+    // Open release calendar on current month
+    pub fn enter_calendar_mode(&mut self) {
+        let today = Date::today();
+        self.calendar_month = (today.year, today.month);
+        self.input_mode = InputMode::Calendar;
+    }
+
+    pub fn shift_calendar_month(&mut self, offset: i32) {
+        let (year, month) = self.calendar_month;
+        self.calendar_month = shift_month(year, month, offset);
+    }
+
+    // Show only machines released in month shown by calendar
+    pub fn apply_calendar_filter(&mut self) {
+        self.push_filter_history();
+        self.release_month_filter = Some(self.calendar_month);
+        self.input_mode = InputMode::Normal;
+        self.state.select(None);
+        self.update_input_fields();
+    }
+
+    pub fn copy_value(&mut self, value: String) {
+        match copy_to_clipboard(&value) {
+            Ok(()) => {
+                self.info_message = format!("Copied {} to clipboard", value);
+                self.clipboard_history.retain(|(previous, _)| *previous != value);
+                self.clipboard_history.push_front((value, Instant::now()));
+                self.clipboard_history.truncate(CLIPBOARD_HISTORY_LIMIT);
+            }
+            Err(e) => {
+                self.info_message = format!("Error copying to clipboard: {}", e);
+            }
+        }
+    }
+
+    pub fn copy_machine_ip(&mut self) {
+        match self.highlighted_machine().and_then(|machine| machine.ip) {
+            Some(ip) => self.copy_value(ip),
+            None => self.info_message = "Selected machine has no IP".to_string(),
+        }
+    }
+
+    pub fn enter_clipboard_history_mode(&mut self) {
+        if self.clipboard_history.is_empty() {
+            self.info_message = "Clipboard history is empty".to_string();
+            return;
+        }
+        self.clipboard_state.select(Some(0));
+        self.input_mode = InputMode::ClipboardHistory;
+    }
+
+    pub fn next_clipboard_entry(&mut self) {
+        let i = self.clipboard_state.selected().map_or(0, |i| (i + 1) % self.clipboard_history.len());
+        self.clipboard_state.select(Some(i));
+    }
+
+    pub fn previous_clipboard_entry(&mut self) {
+        let len = self.clipboard_history.len();
+        let i = self.clipboard_state.selected().map_or(0, |i| (i + len - 1) % len);
+        self.clipboard_state.select(Some(i));
+    }
+
+    // Copy history entry again, which also moves it to top of history
+    pub fn recopy_clipboard_entry(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let entry = self.clipboard_state
+            .selected()
+            .and_then(|i| self.clipboard_history.get(i))
+            .map(|(value, _)| value.clone());
+        if let Some(value) = entry {
+            self.copy_value(value);
+        }
+    }
+
+    pub fn enter_command_mode(&mut self) {
+        self.command_input.clear();
+        self.command_error = None;
+        self.input_mode = InputMode::Command;
+    }
+
+    pub fn complete_command_input(&mut self) {
+        if let Some(name) = complete_command(&self.command_input) {
+            self.command_input = format!("{} ", name);
+        }
+    }
+
+    pub fn execute_command(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let input = self.command_input.trim().to_string();
+        self.command_input.clear();
+
+        match parse_command(&input) {
+            Some(AppCommand::Quit) => self.quit(),
+            Some(AppCommand::Filter(criteria)) => {
+                self.filter_criteria = criteria;
+                self.category_filter = None;
+                self.state.select(None);
+                self.update_input_fields();
+            }
+            Some(AppCommand::Sort(criteria)) => {
+                self.sort_criteria = criteria;
+                self.state.select(None);
+                self.update_input_fields();
+            }
+            Some(AppCommand::Spawn) => self.request_spawn_machine(),
+            Some(AppCommand::Terminate) => self.request_terminate_machine(),
+            Some(AppCommand::Refresh) => self.request_fetch_machines(),
+            Some(AppCommand::ExportJson(path)) => self.export_machines_json(&path),
+            None => self.command_error = Some(format!("Unknown command: {}", input)),
+        }
+    }
+
+    // Write tagged machines, or all as currently filtered and sorted, to JSON file
+    pub fn export_machines_json(&mut self, path: &Path) {
+        let mut machines = self.sorted_machines(self.filtered_machines());
+        if !self.selected_machines.is_empty() {
+            machines.retain(|m| self.selected_machines.contains(&m.id));
+        }
+        let result = serde_json::to_string_pretty(&machines)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+
+        self.info_message = match result {
+            Ok(()) => format!("Exported {} machines to {}", machines.len(), path.display()),
+            Err(e) => format!("Error exporting machines: {}", e),
+        };
+    }
+
+    // Machine under list cursor
+    pub fn highlighted_machine(&self) -> Option<MachineSummary> {
+        let selected = self.state.selected()?;
+        let sorted = self.sorted_machines(self.filtered_machines());
+        sorted.get(selected).cloned()
+    }
+
     pub fn set_info_message(&mut self, message: String) {
         self.info_message = message;
     }
 }
 
-pub async fn fetch_all_machines(client: &Client, htb_api_key: &str, sender: &UnboundedSender<Event>) -> AppResult<()> {
+// Insert all ids, or remove them when all were present. Returns true when removed
+fn toggle_all(set: &mut HashSet<u64>, ids: &[u64]) -> bool {
+    let remove = !ids.is_empty() && ids.iter().all(|id| set.contains(id));
+    for id in ids {
+        if remove {
+            set.remove(id);
+        } else {
+            set.insert(*id);
+        }
+    }
+    remove
+}
+
+// Braces are only valid as part of HTB{...} wrapper, so plain hex flags pass too
+pub fn validate_flag_chars(flag: &str) -> Vec<CharStatus> {
+    let wrapped = flag.starts_with("HTB{");
+    let last = flag.chars().count().saturating_sub(1);
+    flag.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let valid = match c {
+                '{' => wrapped && i == 3,
+                '}' => wrapped && i == last && i > 4,
+                c => c.is_ascii_alphanumeric() || "_!@#$%^&*".contains(c),
+            };
+            if valid { CharStatus::Valid } else { CharStatus::Invalid }
+        })
+        .collect()
+}
+
+// Map IPs to hostnames from hosts file, unreadable file gives empty map
+pub fn parse_hosts_file(path: &Path) -> HashMap<IpAddr, Vec<String>> {
+    let mut hosts: HashMap<IpAddr, Vec<String>> = HashMap::new();
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return hosts;
+    };
+
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        if let Some(ip) = fields.next().and_then(|ip| ip.parse::<IpAddr>().ok()) {
+            hosts.entry(ip).or_default().extend(fields.map(String::from));
+        }
+    }
+
+    hosts
+}
+
+// Message shown to user for a failed HTB API request
+pub fn request_error_message(e: &reqwest::Error) -> String {
+    if e.is_timeout() {
+        "Request timed out — is HTB API reachable?".to_string()
+    } else {
+        format!("Network request failed: {}", e)
+    }
+}
+
+pub fn app_error_message(e: &(dyn error::Error + 'static)) -> String {
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(request_error) => request_error_message(request_error),
+        None => e.to_string(),
+    }
+}
+
+// Id of user owning application key
+pub async fn fetch_user_id_from_key(client: &HTBClient) -> AppResult<u64> {
+    let url = format!("{}/user/info", HTB_API_URL);
+    let json = client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?;
+
+    json.get("info")
+        .and_then(|info| info.get("id"))
+        .and_then(Value::as_u64)
+        .ok_or_else(|| "Missing user id in user info response".into())
+}
+
+// Stats and achievements come from the same profile response
+pub async fn fetch_user_profile(client: &HTBClient, user_id: u64) -> AppResult<(UserStats, Vec<UserAchievement>)> {
+    let url = format!("{}/user/profile/basic/{}", HTB_API_URL, user_id);
+    let json = client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?;
+
+    let profile = json.get("profile").ok_or("Missing profile in user profile response")?;
+    let stats: UserStats = serde_json::from_value(profile.clone())?;
+    let achievements = profile.get("achievements")
+        .or_else(|| json.get("achievements"))
+        .cloned()
+        .unwrap_or(Value::Array(Vec::new()));
+    let mut achievements: Vec<UserAchievement> = serde_json::from_value(achievements)?;
+    achievements.sort_by(|a, b| b.date.cmp(&a.date)); // ISO dates sort as text
+    Ok((stats, achievements))
+}
+
+// Any HTTP response counts as reachable, only transport errors mean offline
+pub async fn check_api_reachable(client: &HTBClient) -> bool {
+    let url = format!("{}/user/info", HTB_API_URL);
+    client.get(&url).send().await.is_ok()
+}
+
+pub async fn fetch_all_machines(client: &HTBClient, sender: &UnboundedSender<Event>, generation: u64) -> AppResult<()> {
     // Fetch active machines
     let url = format!("{}/machine/paginated?per_page=100", HTB_API_URL);
-    let res = fetch_machines(client, htb_api_key, &url).await?;
-    sender.send(Event::FetchMachinesResult(Ok((res.data, Ok(()))))).unwrap();
+    let res = fetch_machines(client, &url, sender).await?;
+    sender.send(Event::FetchMachinesResult(generation, res.data)).unwrap();
 
     // Fetch retired machines
     let url = format!("{}/machine/list/retired/paginated?per_page=100", HTB_API_URL);
-    let mut res = fetch_machines(client, htb_api_key, &url).await?;
-    sender.send(Event::FetchMachinesResult(Ok((res.data, Ok(()))))).unwrap();    
+    let mut res = fetch_machines(client, &url, sender).await?;
+    mark_retired(&mut res.data);
+    sender.send(Event::FetchMachinesResult(generation, res.data)).unwrap();
 
     while let Some(next_url) = res.links.next {
-        res = fetch_machines(client, htb_api_key, &next_url).await?;
-        sender.send(Event::FetchMachinesResult(Ok((res.data, Ok(()))))).unwrap();
+        res = fetch_machines(client, &next_url, sender).await?;
+        mark_retired(&mut res.data);
+        sender.send(Event::FetchMachinesResult(generation, res.data)).unwrap();
     }
 
     Ok(())
 }
 
-pub async fn fetch_machines(client: &Client, htb_api_key: &str, url: &str) -> AppResult<Root> {
-    let res = client
+fn mark_retired(machines: &mut [MachineSummary]) {
+    for machine in machines {
+        machine.retired = true;
+    }
+}
+
+pub async fn fetch_machines(client: &HTBClient, url: &str, sender: &UnboundedSender<Event>) -> AppResult<Root> {
+    let raw = client
         .get(url)
-        .header("Authorization", format!("Bearer {}", htb_api_key))
         .send()
         .await?
-        .json::<Root>()
+        .json::<RawRoot>()
         .await?;
-    
+
+    // Skip malformed machines instead of failing the whole page
+    let data = raw.data
+        .into_iter()
+        .filter_map(|value| {
+            let result = serde_json::from_value::<MachineSummary>(value)
+                .map_err(|e| e.to_string())
+                .and_then(|machine| match machine.validate() {
+                    Ok(()) => Ok(machine),
+                    Err(e) => Err(format!("machine {}: {}", machine.id, e)),
+                });
+            match result {
+                Ok(machine) => Some(machine),
+                Err(e) => {
+                    sender.send(Event::UpdateInfoMessage(format!("Skipped malformed machine: {}", e))).unwrap();
+                    None
+                }
+            }
+        })
+        .collect();
+    let res = Root { data, links: raw.links };
+
     // Populate with IP because by default paginated does not have information about IP
     let mut res_with_ip = res;
     for machine in &mut res_with_ip.data {
         if machine.is_active() {
-            match client.get(format!("{}/machine/profile/{}", HTB_API_URL, machine.id))
-                .header("Authorization", format!("Bearer {}", htb_api_key))
+            match client.get(&format!("{}/machine/profile/{}", HTB_API_URL, machine.id))
                 .send()
                 .await
                 {
                     Ok(response) => {
                         if let Ok(json) = response.json::<Value>().await {
-                            if let Some(ip) = json.get("info").and_then(|info| info.get("ip")).and_then(Value::as_str) {
+                            let info = json.get("info");
+                            if let Some(ip) = info.and_then(|info| info.get("ip")).and_then(Value::as_str) {
                                 machine.ip = Some(ip.to_string());
                             }
+                            if let Some(category) = info.and_then(|info| info.get("category_name")).and_then(Value::as_str) {
+                                machine.category = Some(category.to_string());
+                            }
+                            if let Some(region) = info.and_then(|info| info.get("server_region")).and_then(Value::as_str) {
+                                machine.server_region = Some(region.to_string());
+                            }
                         }
                     },
                     Err(e) => {
@@ -373,19 +1793,45 @@ pub async fn fetch_machines(client: &Client, htb_api_key: &str, url: &str) -> Ap
 }
 
 
-pub async fn spawn_machine(client: &Client, htb_api_key: &str, machine_id: u64) -> Result<(u64, String), String> {
+pub async fn fetch_machine_detail(client: &HTBClient, machine_id: u64) -> Result<Box<MachineDetail>, String> {
+    let url = format!("{}/machine/profile/{}", HTB_API_URL, machine_id);
+    let res = client
+        .get(&url)
+        .send()
+        .await;
+
+    match res {
+        Ok(response) => {
+            if response.status().is_success() {
+                let json = response
+                    .json::<Value>()
+                    .await
+                    .map_err(|_| "Failed to parse machine profile JSON".to_string())?;
+                let info = json.get("info").cloned().unwrap_or_default();
+                serde_json::from_value::<MachineDetail>(info)
+                    .map(Box::new)
+                    .map_err(|e| format!("Unexpected machine profile format: {}", e))
+            } else {
+                Err(format!("Failed to get profile with status: {}", response.status()))
+            }
+        }
+        Err(e) => {
+            Err(request_error_message(&e))
+        }
+    }
+}
+
+pub async fn spawn_machine(client: &HTBClient, machine_id: u64) -> Result<(u64, String), String> {
     let url = format!("{}/vm/spawn/?machine_id={}", HTB_API_URL, machine_id);
     let res = client
-        .post(url)
-        .header("Authorization", format!("Bearer {}", htb_api_key))
+        .post(&url)
         .send()
         .await;
 
     match res {
         Ok(response) => {
             if response.status().is_success() {
-                match client.get(format!("{}/machine/profile/{}", HTB_API_URL, machine_id))
-                    .header("Authorization", format!("Bearer {}", htb_api_key))
+                match client.get(&format!("{}/machine/profile/{}", HTB_API_URL, machine_id))
                     .send()
                     .await
                 {
@@ -404,28 +1850,76 @@ pub async fn spawn_machine(client: &Client, htb_api_key: &str, machine_id: u64)
                             Err(format!("Failed to get profile with status: {}", profile_response.status()))
                         }
                     },
-                Err(e) => Err(format!("Network request for machine profile failed: {}", e))
+                Err(e) => Err(request_error_message(&e))
                 }
             } else {
                 Err(format!("Failed to spawn with status: {}", response.status()))
             }
         }
         Err(e) => {
-            Err(format!("Network request failed: {}", e))
+            Err(request_error_message(&e))
         }
     }
 }
 
-pub async fn submit_flag(client: &Client, htb_api_key: &str, machine_id: u64, flag: &str) ->Result<String, String> {
-    let url = format!("{}/machine/own", HTB_API_URL);
+pub async fn terminate_machine(client: &HTBClient, machine_id: u64) -> Result<u64, String> {
+    let url = format!("{}/vm/terminate", HTB_API_URL);
     let payload = json!({
+        "machine_id": machine_id
+    });
+
+    let res = client
+        .post(&url)
+        .json(&payload)
+        .send()
+        .await;
+    match res {
+        Ok(response) => {
+            if response.status().is_success() {
+                Ok(machine_id)
+            } else {
+                Err(format!("Failed to terminate with status: {}", response.status()))
+            }
+        }
+        Err(e) => {
+            Err(request_error_message(&e))
+        }
+    }
+}
+
+pub async fn submit_writeup(client: &HTBClient, machine_id: u64, url: String) -> Result<(u64, String), String> {
+    let endpoint = format!("{}/machine/writeup/{}", HTB_API_URL, machine_id);
+    let res = client
+        .post(&endpoint)
+        .json(&json!({ "url": url }))
+        .send()
+        .await;
+    match res {
+        Ok(response) => {
+            if response.status().is_success() {
+                Ok((machine_id, url))
+            } else {
+                Err(format!("Failed to submit writeup with status: {}", response.status()))
+            }
+        }
+        Err(e) => {
+            Err(request_error_message(&e))
+        }
+    }
+}
+
+pub async fn submit_flag(client: &HTBClient, machine_id: u64, flag: &str, flag_type: Option<FlagType>) ->Result<(u64, Option<FlagType>, String), String> {
+    let url = format!("{}/machine/own", HTB_API_URL);
+    let mut payload = json!({
         "id": machine_id,
         "flag": flag
     });
+    if let Some(flag_type) = flag_type {
+        payload["type"] = json!(flag_type.as_str()); // Ignored by API versions without flag types
+    }
 
     let res = client
-        .post(url)
-        .header("Authorization", format!("Bearer {}", htb_api_key))
+        .post(&url)
         .header("Content-Type", "application/json")
         .json(&payload)
         .send()
@@ -433,13 +1927,275 @@ pub async fn submit_flag(client: &Client, htb_api_key: &str, machine_id: u64, fl
     match res {
         Ok(response) => {
             if response.status().is_success() {
-                Ok(format!("Flag for machine {} is correct", machine_id))
+                Ok((machine_id, flag_type, format!("Flag for machine {} is correct", machine_id)))
             } else {
                 Err(format!("Wrong flag for machine {}", machine_id))
             }
         }
         Err(e) => {
-            Err(format!("Network request failed: {}", e))
+            Err(request_error_message(&e))
         }
     }
 }
+
+pub async fn download_vpn_config(client: &HTBClient, server_type: &str, output_path: &Path) -> AppResult<()> {
+    let url = format!("{}/connection/download?type={}", HTB_API_URL, server_type);
+    let bytes = client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    if let Some(parent) = output_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(output_path, &bytes).await?;
+
+    Ok(())
+}
+
+// Round trip time in ms of a single ICMP echo using system ping
+pub async fn ping_machine(ip: &str) -> Result<f64, String> {
+    let output = tokio::process::Command::new("ping")
+        .args(["-c", "1", "-W", "2", ip])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ping: {}", e))?;
+
+    if !output.status.success() {
+        return Err("unreachable".to_string());
+    }
+
+    // Reply line looks like "64 bytes from 10.10.11.5: icmp_seq=1 ttl=63 time=45.3 ms"
+    String::from_utf8_lossy(&output.stdout)
+        .split("time=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|time| time.parse().ok())
+        .ok_or_else(|| "Failed to parse ping output".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    fn test_app() -> (App, UnboundedReceiver<Event>) {
+        // Keep persisted history out of real config dir
+        static CONFIG_HOME: std::sync::Once = std::sync::Once::new();
+        CONFIG_HOME.call_once(|| std::env::set_var("XDG_CONFIG_HOME", std::env::temp_dir().join("htb-tui-test")));
+        let (sender, receiver) = unbounded_channel();
+        let mut app = App::new(String::new(), Config::default(), sender);
+        // Ignore what earlier tests saved
+        app.owns_snapshots.clear();
+        app.first_difficulties.clear();
+        (app, receiver)
+    }
+
+    fn machine(id: u64, name: &str) -> MachineSummary {
+        MachineSummary { id, name: name.to_string(), ..Default::default() }
+    }
+
+    fn machine_ids(app: &App) -> Vec<u64> {
+        app.machines.iter().map(|m| m.id).collect()
+    }
+
+    #[test]
+    fn merge_updates_in_place_and_appends_new() {
+        let (mut app, _receiver) = test_app();
+        let mut first = machine(1, "Alpha");
+        first.category = Some("Web".to_string());
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![first, machine(2, "Bravo")]);
+        app.handle_fetch_machines_done(generation, Ok(()));
+
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(3, "Charlie"), machine(1, "Alpha2")]);
+
+        assert_eq!(machine_ids(&app), vec![1, 2, 3]);
+        let updated = app.machine_by_id(1).unwrap();
+        assert_eq!(updated.name, "Alpha2");
+        assert_eq!(updated.category.as_deref(), Some("Web"));
+    }
+
+    #[test]
+    fn complete_fetch_prunes_missing_machines() {
+        let (mut app, _receiver) = test_app();
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(1, "Alpha"), machine(2, "Bravo")]);
+        app.handle_fetch_machines_done(generation, Ok(()));
+
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(2, "Bravo")]);
+        assert_eq!(machine_ids(&app), vec![1, 2]); // Not pruned before completion
+        app.handle_fetch_machines_done(generation, Ok(()));
+
+        assert_eq!(machine_ids(&app), vec![2]);
+        assert!(app.machine_by_id(1).is_none());
+        assert!(app.machine_by_id(2).is_some());
+    }
+
+    #[test]
+    fn failed_fetch_keeps_machines() {
+        let (mut app, _receiver) = test_app();
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(1, "Alpha"), machine(2, "Bravo")]);
+        app.handle_fetch_machines_done(generation, Ok(()));
+
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(2, "Bravo")]);
+        app.handle_fetch_machines_done(generation, Err("timeout".to_string()));
+
+        assert_eq!(machine_ids(&app), vec![1, 2]);
+        assert!(app.info_message.contains("timeout"));
+    }
+
+    #[test]
+    fn refetch_keeps_highlighted_machine() {
+        let (mut app, _receiver) = test_app();
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(1, "Alpha"), machine(2, "Bravo"), machine(3, "Charlie")]);
+        app.handle_fetch_machines_done(generation, Ok(()));
+        let position = app.sorted_machines(app.filtered_machines()).iter().position(|m| m.id == 2);
+        app.state.select(position);
+
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(3, "Charlie"), machine(2, "Bravo")]);
+        app.handle_fetch_machines_done(generation, Ok(()));
+
+        assert_eq!(app.highlighted_machine().map(|m| m.id), Some(2));
+    }
+
+    #[test]
+    fn overlapping_fetches_prune_only_on_latest_done() {
+        let (mut app, _receiver) = test_app();
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(1, "Alpha"), machine(2, "Bravo"), machine(3, "Charlie")]);
+        app.handle_fetch_machines_done(generation, Ok(()));
+
+        // Second fetch starts before first one finishes
+        let older = app.start_machines_fetch();
+        app.handle_fetch_machines_result(older, vec![machine(1, "Alpha")]);
+        let newer = app.start_machines_fetch();
+        app.handle_fetch_machines_result(newer, vec![machine(1, "Alpha"), machine(2, "Bravo")]);
+        app.handle_fetch_machines_done(older, Ok(()));
+        assert_eq!(machine_ids(&app), vec![1, 2, 3]); // Stale completion does not prune
+
+        app.handle_fetch_machines_result(older, vec![machine(4, "Delta")]);
+        assert!(app.machine_by_id(4).is_none()); // Stale batch is dropped
+
+        app.handle_fetch_machines_result(newer, vec![machine(3, "Charlie")]);
+        app.handle_fetch_machines_done(newer, Ok(()));
+        assert_eq!(machine_ids(&app), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn toggle_all_adds_until_all_present_then_removes() {
+        let mut set = HashSet::from([1]);
+        assert!(!toggle_all(&mut set, &[1, 2]));
+        assert_eq!(set, HashSet::from([1, 2]));
+        assert!(toggle_all(&mut set, &[1, 2]));
+        assert!(set.is_empty());
+    }
+
+    fn detail_fetches(receiver: &mut UnboundedReceiver<Event>) -> Vec<u64> {
+        std::iter::from_fn(|| receiver.try_recv().ok())
+            .filter_map(|event| match event {
+                Event::FetchMachineDetail(machine_id) => Some(machine_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detail_fetched_once_after_selection_settles() {
+        let (mut app, mut receiver) = test_app();
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![machine(1, "Alpha"), machine(2, "Bravo")]);
+        app.state.select(None);
+
+        app.next();
+        app.next();
+        app.previous();
+        app.tick();
+        assert!(detail_fetches(&mut receiver).is_empty()); // Still moving
+
+        std::thread::sleep(DETAIL_FETCH_DELAY);
+        app.tick();
+        let highlighted = app.highlighted_machine().unwrap().id;
+        assert_eq!(detail_fetches(&mut receiver), vec![highlighted]);
+
+        app.next();
+        app.previous();
+        std::thread::sleep(DETAIL_FETCH_DELAY);
+        app.tick();
+        assert!(detail_fetches(&mut receiver).is_empty()); // Already in flight
+    }
+
+    #[test]
+    fn cycle_filter_keeps_region_and_reports_cleared_month() {
+        let (mut app, _receiver) = test_app();
+        app.region_filter = Some("EU".to_string());
+        app.release_month_filter = Some((2024, 5));
+
+        app.cycle_filter();
+
+        assert_eq!(app.region_filter.as_deref(), Some("EU"));
+        assert_eq!(app.release_month_filter, None);
+        assert!(app.info_message.contains("Release month filter cleared"));
+    }
+
+    #[test]
+    fn undo_restores_month_and_region_filters() {
+        let (mut app, _receiver) = test_app();
+        app.region_filter = Some("EU".to_string());
+        app.release_month_filter = Some((2024, 5));
+
+        app.cycle_filter();
+        app.undo_last_change();
+
+        assert_eq!(app.filter_criteria, FilterCriteria::None);
+        assert_eq!(app.release_month_filter, Some((2024, 5)));
+        assert_eq!(app.region_filter.as_deref(), Some("EU"));
+    }
+
+    #[test]
+    fn first_listed_difficulty_is_kept_for_drift() {
+        let (mut app, _receiver) = test_app();
+        let mut alpha = machine(1, "Alpha");
+        alpha.difficulty = 40;
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![alpha.clone()]);
+
+        alpha.difficulty = 45;
+        let generation = app.start_machines_fetch();
+        app.handle_fetch_machines_result(generation, vec![alpha]);
+
+        assert_eq!(app.machine_by_id(1).unwrap().difficulty_drift(), Some(5));
+    }
+
+    #[test]
+    fn validate_flag_chars_accepts_wrapped_and_plain_flags() {
+        let all_valid = |flag: &str| validate_flag_chars(flag).iter().all(|s| *s == CharStatus::Valid);
+        assert!(all_valid("HTB{s0me_fl4g!}"));
+        assert!(all_valid("0123456789abcdef0123456789abcdef"));
+        assert_eq!(validate_flag_chars("HTB{}").last(), Some(&CharStatus::Invalid)); // Empty flag body
+        assert_eq!(validate_flag_chars("a{b"), vec![CharStatus::Valid, CharStatus::Invalid, CharStatus::Valid]);
+        assert_eq!(validate_flag_chars("ab\u{200b}"), vec![CharStatus::Valid, CharStatus::Valid, CharStatus::Invalid]);
+    }
+
+    #[test]
+    fn parse_hosts_file_maps_ips_to_names() {
+        let path = std::env::temp_dir().join(format!("htb-tui-hosts-{}", std::process::id()));
+        std::fs::write(&path, "# comment\n10.10.11.5 lame.htb www.lame.htb # box\n10.10.11.5 extra.htb\nnot-an-ip foo\n").unwrap();
+        let hosts = parse_hosts_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let ip: IpAddr = "10.10.11.5".parse().unwrap();
+        assert_eq!(hosts.get(&ip), Some(&vec!["lame.htb".to_string(), "www.lame.htb".to_string(), "extra.htb".to_string()]));
+        assert_eq!(hosts.len(), 1);
+        assert!(parse_hosts_file(Path::new("/nonexistent/hosts")).is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::App;
+use crate::history::daily_totals;
+use crate::ui::centered_rect;
+
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+    text::{Line, Span},
+    Frame,
+};
+
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const HEATMAP_WEEKS: i64 = 52;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32, // 1-12
+    pub day: u32,   // 1-31
+}
+
+impl Date {
+    // Parse leading "YYYY-MM-DD" of API timestamps like "2024-05-11T19:00:00.000000Z"
+    pub fn parse(value: &str) -> Option<Date> {
+        let mut parts = value.get(..10)?.split('-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    pub fn today() -> Date {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Date::from_days((secs / 86_400) as i64)
+    }
+
+    // Days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    pub fn to_days(self) -> i64 {
+        let year = if self.month <= 2 { self.year - 1 } else { self.year } as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+        let year = (year_of_era + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+        Date { year, month, day }
+    }
+
+    // 0 for Monday through 6 for Sunday
+    pub fn weekday(self) -> usize {
+        (self.to_days() + 3).rem_euclid(7) as usize // 1970-01-01 was a Thursday
+    }
+
+    // ISO 8601 week number, week belongs to year of its Thursday
+    pub fn iso_week(self) -> u32 {
+        let thursday = Date::from_days(self.to_days() - self.weekday() as i64 + 3);
+        let day_of_year = thursday.to_days() - Date { year: thursday.year, month: 1, day: 1 }.to_days();
+        (day_of_year / 7) as u32 + 1
+    }
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        _ if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        _ => 28,
+    }
+}
+
+// Month before or after (year, month) by offset
+pub fn shift_month(year: i32, month: u32, offset: i32) -> (i32, u32) {
+    let index = year * 12 + month as i32 - 1 + offset;
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
+
+// Monthly grid with names of machines released on each day
+pub fn render(app: &App, frame: &mut Frame) {
+    let theme = app.current_theme;
+    let (year, month) = app.calendar_month;
+
+    let releases: Vec<(u32, &str)> = app.machines
+        .iter()
+        .filter_map(|machine| {
+            let date = machine.release_parsed()?;
+            (date.year == year && date.month == month).then_some((date.day, machine.name.as_str()))
+        })
+        .collect();
+
+    let area = centered_rect(90, 90, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = format!(
+        "Releases: {} {} ({} machines)  ←/→ month, Enter filter list, Esc close",
+        MONTH_NAMES[month as usize - 1], year, releases.len()
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(Style::default().fg(theme.foreground).bg(theme.background));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let first_weekday = Date { year, month, day: 1 }.weekday();
+    let days = days_in_month(year, month);
+    let weeks = (first_weekday as u32 + days).div_ceil(7) as usize;
+
+    let mut row_constraints = vec![Constraint::Length(1)];
+    row_constraints.extend(std::iter::repeat_n(Constraint::Ratio(1, weeks as u32), weeks));
+    let rows = Layout::vertical(row_constraints).split(inner);
+    let column_constraints = [Constraint::Ratio(1, 7); 7];
+
+    let header_cells = Layout::horizontal(column_constraints).split(rows[0]);
+    for (cell, name) in header_cells.iter().zip(WEEKDAY_NAMES) {
+        frame.render_widget(
+            Paragraph::new(name).style(Style::default().add_modifier(Modifier::BOLD)),
+            *cell,
+        );
+    }
+
+    let today = Date::today();
+    for week in 0..weeks {
+        let cells = Layout::horizontal(column_constraints).split(rows[week + 1]);
+        for (weekday, cell) in cells.iter().enumerate() {
+            let index = (week * 7 + weekday) as i64 - first_weekday as i64;
+            if index < 0 || index >= days as i64 {
+                continue;
+            }
+            let day = index as u32 + 1;
+
+            let day_style = if (Date { year, month, day }) == today {
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default().add_modifier(Modifier::BOLD)
+            };
+            let mut lines = vec![Line::styled(day.to_string(), day_style)];
+            lines.extend(
+                releases
+                    .iter()
+                    .filter(|(release_day, _)| *release_day == day)
+                    .map(|(_, name)| Line::styled(name.to_string(), Style::default().fg(theme.active))),
+            );
+
+            frame.render_widget(
+                Paragraph::new(lines).block(Block::default().borders(Borders::TOP)),
+                *cell,
+            );
+        }
+    }
+}
+
+// GitHub style grid of flags submitted per day over last 52 weeks, one column per week
+pub fn render_activity_heatmap(app: &App, frame: &mut Frame) {
+    let theme = app.current_theme;
+    let area = frame.area();
+    frame.render_widget(Clear, area);
+
+    let today = Date::today();
+    let first_monday = today.to_days() - today.weekday() as i64 - HEATMAP_WEEKS * 7;
+    let days = (today.to_days() - first_monday + 1) as usize;
+    let counts = daily_totals(&app.points_history, days, |_| 1);
+    let weeks = HEATMAP_WEEKS as usize + 1;
+
+    // Labels start at their week column, skipped when previous label still occupies it
+    let mut month_labels = String::new();
+    let mut week_labels = String::new();
+    for week in 0..weeks {
+        let column = 4 + week * 2;
+        let monday = Date::from_days(first_monday + week as i64 * 7);
+        let previous_month = (week > 0).then(|| Date::from_days(monday.to_days() - 7).month);
+        if previous_month != Some(monday.month) && month_labels.len() <= column {
+            month_labels = format!("{:column$}{}", month_labels, &MONTH_NAMES[monday.month as usize - 1][..3]);
+        }
+        if week % 4 == 0 && week_labels.len() <= column {
+            week_labels = format!("{:column$}{}", week_labels, monday.iso_week());
+        }
+    }
+
+    let mut lines = vec![
+        Line::styled(month_labels, Style::default().add_modifier(Modifier::BOLD)),
+        Line::styled(week_labels, Style::default().add_modifier(Modifier::DIM)),
+    ];
+    for (weekday, name) in WEEKDAY_NAMES.iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{} ", name))];
+        for week in 0..weeks {
+            let index = week * 7 + weekday;
+            let cell = match counts.get(index) {
+                Some(count) => Span::styled("█ ", Style::default().fg(heatmap_color(*count))),
+                None => Span::raw("  "), // Future days of current week
+            };
+            spans.push(cell);
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    let mut legend = vec![Span::raw("Less ")];
+    legend.extend([0, 1, 2, 4].map(|count| Span::styled("█ ", Style::default().fg(heatmap_color(count)))));
+    legend.push(Span::raw(format!("More    {} flags in the last year", counts.iter().sum::<u64>())));
+    lines.push(Line::from(legend));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Activity (Esc close)")
+        .style(Style::default().fg(theme.foreground).bg(theme.background));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn heatmap_color(count: u64) -> Color {
+    match count {
+        0 => Color::Rgb(22, 27, 34),
+        1 => Color::Rgb(14, 68, 41),
+        2..=3 => Color::Rgb(38, 166, 65),
+        _ => Color::Rgb(57, 211, 83),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn parse_reads_leading_date_of_timestamp() {
+        assert_eq!(Date::parse("2024-05-11T19:00:00.000000Z"), Some(date(2024, 5, 11)));
+        assert_eq!(Date::parse("2024-02-29"), Some(date(2024, 2, 29)));
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("2024-13-01"), None);
+        assert_eq!(Date::parse("2024-05"), None);
+    }
+
+    #[test]
+    fn days_round_trip_and_weekday() {
+        assert_eq!(date(1970, 1, 1).to_days(), 0);
+        assert_eq!(Date::from_days(19_854), date(2024, 5, 11));
+        for days in [-1, 0, 59, 11_016, 19_854] {
+            assert_eq!(Date::from_days(days).to_days(), days);
+        }
+        assert_eq!(date(1970, 1, 1).weekday(), 3); // Thursday
+        assert_eq!(date(2024, 5, 13).weekday(), 0); // Monday
+    }
+
+    #[test]
+    fn iso_week_uses_year_of_thursday() {
+        assert_eq!(date(2024, 1, 1).iso_week(), 1);
+        assert_eq!(date(2021, 1, 3).iso_week(), 53);
+        assert_eq!(date(2024, 12, 30).iso_week(), 1);
+    }
+
+    #[test]
+    fn month_helpers_handle_leap_years_and_year_wrap() {
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(2023, 4), 30);
+        assert_eq!(shift_month(2024, 1, -1), (2023, 12));
+        assert_eq!(shift_month(2024, 12, 1), (2025, 1));
+        assert_eq!(shift_month(2024, 5, -17), (2022, 12));
+    }
+}
@@ -0,0 +1,9 @@
+use std::io::{self, Write};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+// Copy text to system clipboard with OSC 52 escape sequence, works over SSH in most modern terminals
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()
+}
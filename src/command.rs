@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+use crate::app::{FilterCriteria, SortCriteria};
+use crate::config::expand_home;
+
+// Command names accepted in command mode, used for tab completion
+pub const COMMAND_NAMES: [&str; 7] = ["quit", "filter", "sort", "spawn", "terminate", "refresh", "export"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppCommand {
+    Quit,
+    Filter(FilterCriteria),
+    Sort(SortCriteria),
+    Spawn,
+    Terminate,
+    Refresh,
+    ExportJson(PathBuf),
+}
+
+// Parse command line typed after ':' e.g. "filter rootnotowns" or "export json ~/machines.json"
+pub fn parse_command(input: &str) -> Option<AppCommand> {
+    let mut parts = input.split_whitespace();
+    let name = parts.next()?;
+    let args: Vec<&str> = parts.collect();
+
+    match (name, args.as_slice()) {
+        ("quit" | "q", []) => Some(AppCommand::Quit),
+        ("filter", [criteria]) => parse_filter(criteria).map(AppCommand::Filter),
+        ("sort", [criteria]) => parse_sort(criteria).map(AppCommand::Sort),
+        ("spawn", []) => Some(AppCommand::Spawn),
+        ("terminate", []) => Some(AppCommand::Terminate),
+        ("refresh", []) => Some(AppCommand::Refresh),
+        ("export", ["json", path]) => Some(AppCommand::ExportJson(expand_home(path))),
+        _ => None,
+    }
+}
+
+// Complete command name when typed prefix matches exactly one command
+pub fn complete_command(input: &str) -> Option<&'static str> {
+    if input.contains(char::is_whitespace) {
+        return None;
+    }
+    let mut matches = COMMAND_NAMES.iter().filter(|name| name.starts_with(input));
+    match (matches.next(), matches.next()) {
+        (Some(name), None) => Some(name),
+        _ => None,
+    }
+}
+
+fn parse_filter(name: &str) -> Option<FilterCriteria> {
+    match name.to_lowercase().as_str() {
+        "none" => Some(FilterCriteria::None),
+        "usernotowns" => Some(FilterCriteria::UserNotOwns),
+        "rootnotowns" => Some(FilterCriteria::RootNotOwns),
+        "userandrootnotowns" => Some(FilterCriteria::UserAndRootNotOwns),
+        _ => None,
+    }
+}
+
+fn parse_sort(name: &str) -> Option<SortCriteria> {
+    match name.to_lowercase().as_str() {
+        "difficulty" => Some(SortCriteria::Difficulty),
+        "userowns" => Some(SortCriteria::UserOwns),
+        "rootowns" => Some(SortCriteria::RootOwns),
+        "name" => Some(SortCriteria::Name),
+        "category" => Some(SortCriteria::Category),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_command_reads_names_and_arguments() {
+        assert_eq!(parse_command("q"), Some(AppCommand::Quit));
+        assert_eq!(parse_command("filter RootNotOwns"), Some(AppCommand::Filter(FilterCriteria::RootNotOwns)));
+        assert_eq!(parse_command("sort userowns"), Some(AppCommand::Sort(SortCriteria::UserOwns)));
+        assert_eq!(parse_command("export json /tmp/machines.json"), Some(AppCommand::ExportJson(PathBuf::from("/tmp/machines.json"))));
+        assert_eq!(parse_command("filter"), None);
+        assert_eq!(parse_command("spawn now"), None);
+        assert_eq!(parse_command("export csv out.csv"), None);
+        assert_eq!(parse_command(""), None);
+    }
+
+    #[test]
+    fn complete_command_needs_unique_prefix() {
+        assert_eq!(complete_command("ex"), Some("export"));
+        assert_eq!(complete_command("s"), None); // sort and spawn
+        assert_eq!(complete_command("sp"), Some("spawn"));
+        assert_eq!(complete_command("sort "), None);
+    }
+}
@@ -0,0 +1,182 @@
+use std::{collections::HashMap, env, fs, path::PathBuf};
+use crate::themes::Theme;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+// User settings loaded from ~/.config/htb-tui/config.toml
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub max_active_machines: u8, // Simultaneous instances allowed by subscription (1 free, 2 VIP)
+    pub use_unicode_icons: bool, // Show OS as emoji instead of abbreviation
+    pub wrap_navigation: bool, // Jump to other end of list when moving past first or last machine
+    pub theme: Theme,
+    pub request_timeout_secs: u64, // Whole request timeout for HTB API calls
+    pub connect_timeout_secs: u64,
+    pub auto_ping: bool, // Periodically measure latency to active machine
+    pub vpn_server_type: String, // Server type passed to VPN config download
+    pub vpn_config_path: PathBuf, // Where downloaded .ovpn file is written
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_active_machines: 1,
+            use_unicode_icons: true,
+            wrap_navigation: true,
+            theme: Theme::default(),
+            request_timeout_secs: 30,
+            connect_timeout_secs: 10,
+            auto_ping: false,
+            vpn_server_type: "labs".to_string(),
+            vpn_config_path: config_dir()
+                .unwrap_or_default()
+                .join("htb.ovpn"),
+        }
+    }
+}
+
+impl Config {
+    // Load config from disk, missing file or unknown keys fall back to defaults
+    pub fn load() -> Self {
+        config_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(CONFIG_FILE_NAME)).ok())
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let values = parse_key_values(contents);
+        let mut config = Self::default();
+
+        if let Some(max) = values.get("machines.max_active_machines").and_then(|v| v.parse().ok()) {
+            config.max_active_machines = max;
+        }
+        if let Some(use_icons) = values.get("ui.use_unicode_icons").and_then(|v| v.parse().ok()) {
+            config.use_unicode_icons = use_icons;
+        }
+        if let Some(wrap) = values.get("ui.wrap_navigation").and_then(|v| v.parse().ok()) {
+            config.wrap_navigation = wrap;
+        }
+        if let Some(theme) = values.get("ui.theme").and_then(|v| Theme::from_name(v)) {
+            config.theme = theme;
+        }
+        if let Some(timeout) = values.get("network.request_timeout_secs").and_then(|v| v.parse().ok()) {
+            config.request_timeout_secs = timeout;
+        }
+        if let Some(timeout) = values.get("network.connect_timeout_secs").and_then(|v| v.parse().ok()) {
+            config.connect_timeout_secs = timeout;
+        }
+        if let Some(auto_ping) = values.get("network.auto_ping").and_then(|v| v.parse().ok()) {
+            config.auto_ping = auto_ping;
+        }
+        if let Some(server_type) = values.get("vpn.server_type") {
+            config.vpn_server_type = server_type.clone();
+        }
+        if let Some(path) = values.get("vpn.output_path") {
+            config.vpn_config_path = expand_home(path);
+        }
+
+        config
+    }
+}
+
+// Directory holding config and other persistent files
+pub fn config_dir() -> Option<PathBuf> {
+    env::var("XDG_CONFIG_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| env::var("HOME").ok().map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("htb-tui"))
+}
+
+// Replace leading ~ with home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+// Minimal TOML subset: [section] headers, key = value pairs and # comments.
+// Keys are returned as "section.key"
+fn parse_key_values(contents: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut section = String::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let key = if section.is_empty() {
+                key.trim().to_string()
+            } else {
+                format!("{}.{}", section, key.trim())
+            };
+            let value = strip_comment(value).trim().trim_matches('"').to_string();
+            values.insert(key, value);
+        }
+    }
+
+    values
+}
+
+// Drop trailing "# ..." unless '#' is inside a quoted string
+fn strip_comment(value: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &value[..i],
+            _ => {}
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_comments_are_stripped_outside_quotes() {
+        let values = parse_key_values("theme = \"light\" # comment\n[vpn]\nserver = \"eu#1\" # pick one\n");
+        assert_eq!(values.get("theme").map(String::as_str), Some("light"));
+        assert_eq!(values.get("vpn.server").map(String::as_str), Some("eu#1"));
+    }
+
+    #[test]
+    fn parse_reads_sections_and_keeps_defaults() {
+        let config = Config::parse("\
+# htb-tui settings
+[machines]
+max_active_machines = 2
+
+[ui]
+theme = \"light\"
+use_unicode_icons = maybe
+
+[vpn]
+server_type = \"release_arena\"
+");
+        assert_eq!(config.max_active_machines, 2);
+        assert_eq!(config.theme, Theme::LIGHT);
+        assert!(config.use_unicode_icons); // Invalid value falls back to default
+        assert_eq!(config.vpn_server_type, "release_arena");
+        assert_eq!(config.request_timeout_secs, Config::default().request_timeout_secs);
+    }
+
+    #[test]
+    fn expand_home_only_replaces_leading_tilde() {
+        let home = env::var("HOME").unwrap();
+        assert_eq!(expand_home("~/htb.ovpn"), PathBuf::from(home).join("htb.ovpn"));
+        assert_eq!(expand_home("/tmp/~/htb.ovpn"), PathBuf::from("/tmp/~/htb.ovpn"));
+    }
+}
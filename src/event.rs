@@ -1,4 +1,4 @@
-use crate::app::{AppResult, Machine};
+use crate::app::{AppResult, FlagType, MachineDetail, MachineSummary, UserAchievement, UserStats};
 
 use std::time::Duration;
 use crossterm::event::{Event as CrosstermEvent, KeyEvent, MouseEvent};
@@ -11,21 +11,39 @@ pub enum Event {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Resize(u16, u16),
+    FetchUserId,
+    FetchUserIdResult(Result<u64, String>),
+    FetchUserStats, // Also yields achievements, both come from the same profile response
+    FetchUserStatsResult(Result<UserStats, String>),
+    FetchAchievementsResult(Result<Vec<UserAchievement>, String>),
     FetchMachines,
-    FetchMachinesResult(Result<(Vec<Machine>, Result<(), String>), String>),
+    FetchMachinesResult(u64, Vec<MachineSummary>), // Fetch generation, page of machines
+    FetchMachinesDone(u64, Result<(), String>),
+    FetchMachineDetail(u64),
+    FetchMachineDetailResult(u64, Result<Box<MachineDetail>, String>),
     SpawnMachine(u64),
     SpawnMachineResult(Result<(u64, String), String>),
+    TerminateMachine(u64),
+    TerminateMachineResult(Result<u64, String>),
     UpdateList,
-    SubmitFlag(u64, String),
-    SubmitFlagResult(Result<String, String>),
+    SubmitFlag(u64, String, Option<FlagType>),
+    SubmitFlagResult(Result<(u64, Option<FlagType>, String), String>),
+    SubmitWriteup(u64, String),
+    SubmitWriteupResult(Result<(u64, String), String>),
     UpdateInfoMessage(String),
+    DownloadVpnConfig,
+    DownloadVpnConfigResult(Result<(), String>),
+    PingMachine(String),
+    PingMachineResult(String, Result<f64, String>),
+    CheckReachability,
+    CheckReachabilityResult(bool),
 }
 
 #[derive(Debug)]
 pub struct EventHandler {
     pub sender: mpsc::UnboundedSender<Event>,
     receiver: mpsc::UnboundedReceiver<Event>,
-    handler: tokio::task::JoinHandle<()>,
+    _handler: tokio::task::JoinHandle<()>,
 }
 
 impl EventHandler {
@@ -73,7 +91,7 @@ impl EventHandler {
         Self {
             sender,
             receiver,
-            handler,
+            _handler: handler,
         }
     }
 
@@ -81,8 +99,7 @@ impl EventHandler {
         self.receiver
             .recv()
             .await
-            .ok_or(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
+            .ok_or(Box::new(std::io::Error::other(
                         "This is an IO error",
             )))
     }
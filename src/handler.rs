@@ -1,24 +1,63 @@
 use crate::app::{App, AppResult, InputMode};
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) ->AppResult<()> {
+    // Command errors stay visible until next key press
+    if app.input_mode == InputMode::Normal {
+        app.command_error = None;
+    }
+
     match app.input_mode {
+        InputMode::Normal if app.fullscreen_detail => match key_event.code {
+            KeyCode::Char('F') | KeyCode::Esc => app.toggle_fullscreen_detail(),
+            KeyCode::Char('j') | KeyCode::Down => app.scroll_detail(1),
+            KeyCode::Char('k') | KeyCode::Up => app.scroll_detail(-1),
+            KeyCode::Char('q') => app.quit(),
+            _ => {}
+        },
         InputMode::Normal => match key_event.code {
+            KeyCode::Char('d') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.cycle_list_density(),
+            KeyCode::Char('k') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.enter_calendar_mode(),
+            KeyCode::Char('y') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.enter_clipboard_history_mode(),
+            KeyCode::Char('s') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.enter_secondary_sort_mode(),
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.cycle_region_filter(),
+            KeyCode::Char('a') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.enter_achievements_mode(),
+            KeyCode::Char('h') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.input_mode = InputMode::ActivityHeatmap;
+            }
+            // Terminals send Ctrl+I as Tab
+            KeyCode::Tab => {
+                app.input_mode = InputMode::Metrics;
+            }
+            KeyCode::Char('z') if key_event.modifiers.contains(KeyModifiers::CONTROL) => app.undo_last_change(),
             KeyCode::Char('q') => app.quit(),
             KeyCode::Char('f') => app.cycle_filter(),
+            KeyCode::Char('F') => app.toggle_fullscreen_detail(),
+            KeyCode::Char('c') => app.cycle_category_filter(),
             KeyCode::Char('s') => app.cycle_sort(),
             KeyCode::Down => app.next(),
             KeyCode::Up => app.previous(),
             KeyCode::Char('a') => app.enter_flag_input_mode(),
+            KeyCode::Char(':') => app.enter_command_mode(),
+            KeyCode::Char('/') => app.enter_search_mode(),
+            KeyCode::Char('v') => app.request_download_vpn_config(),
+            KeyCode::Char('y') => app.copy_machine_ip(),
+            KeyCode::Char(' ') => app.toggle_machine_selection(),
+            KeyCode::Char('b') => app.bookmark_selected_machines(),
+            KeyCode::Char('w') => app.wishlist_selected_machines(),
+            KeyCode::Char('x') => app.enter_export_mode(),
+            KeyCode::Esc => app.clear_machine_selection(),
             KeyCode::Enter => {
                 app.request_spawn_machine();
             }
+            // Unbound letters start a search for typed machine name
+            KeyCode::Char(c) if c.is_alphabetic() && !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.enter_search_mode_with_char(c);
+            }
             _ => {}
         },
         InputMode::Flag => match key_event.code {
-            KeyCode::Esc => {
-                app.input_mode = InputMode::Normal;
-            }
+            KeyCode::Esc => app.cancel_flag_input(),
             KeyCode::Char(c) => {
                 app.flag_input.push(c);
             }
@@ -30,6 +69,85 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) ->AppResult<()> {
             }
             _ => {}
         },
+        InputMode::WriteupUrl => match key_event.code {
+            KeyCode::Esc => app.cancel_writeup_input(),
+            KeyCode::Char(c) => {
+                app.writeup_input.push(c);
+            }
+            KeyCode::Backspace => {
+                app.writeup_input.pop();
+            }
+            KeyCode::Enter => app.request_submit_writeup(),
+            _ => {}
+        },
+        InputMode::Confirm => match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => app.accept_confirm(),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.reject_confirm(),
+            _ => {}
+        },
+        InputMode::Command => match key_event.code {
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char(c) => {
+                app.command_input.push(c);
+            }
+            KeyCode::Backspace => {
+                app.command_input.pop();
+            }
+            KeyCode::Tab => app.complete_command_input(),
+            KeyCode::Enter => app.execute_command(),
+            _ => {}
+        },
+        InputMode::Search => match key_event.code {
+            KeyCode::Esc => app.cancel_search(),
+            KeyCode::Enter => {
+                app.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char(c) => app.push_search_char(c),
+            KeyCode::Backspace => app.pop_search_char(),
+            _ => {}
+        },
+        InputMode::Calendar => match key_event.code {
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            KeyCode::Left => app.shift_calendar_month(-1),
+            KeyCode::Right => app.shift_calendar_month(1),
+            KeyCode::Enter => app.apply_calendar_filter(),
+            _ => {}
+        },
+        InputMode::ClipboardHistory => match key_event.code {
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            KeyCode::Down => app.next_clipboard_entry(),
+            KeyCode::Up => app.previous_clipboard_entry(),
+            KeyCode::Enter => app.recopy_clipboard_entry(),
+            _ => {}
+        },
+        InputMode::SecondarySort => match key_event.code {
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            KeyCode::Down => app.next_secondary_sort(),
+            KeyCode::Up => app.previous_secondary_sort(),
+            KeyCode::Enter => app.apply_secondary_sort(),
+            _ => {}
+        },
+        InputMode::Achievements => match key_event.code {
+            KeyCode::Esc => {
+                app.input_mode = InputMode::Normal;
+            }
+            KeyCode::Down => app.next_achievement(),
+            KeyCode::Up => app.previous_achievement(),
+            _ => {}
+        },
+        InputMode::ActivityHeatmap | InputMode::Metrics => {
+            if key_event.code == KeyCode::Esc {
+                app.input_mode = InputMode::Normal;
+            }
+        }
     }
     Ok(())
 }
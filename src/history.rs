@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use crate::config::config_dir;
+
+const POINTS_HISTORY_FILE_NAME: &str = "points_history.json";
+const WRITEUP_URLS_FILE_NAME: &str = "writeups.json";
+const OWNS_SNAPSHOTS_FILE_NAME: &str = "owns_snapshots.json";
+const BOOKMARKS_FILE_NAME: &str = "bookmarks.json";
+const WISHLIST_FILE_NAME: &str = "wishlist.json";
+const FIRST_DIFFICULTIES_FILE_NAME: &str = "first_difficulties.json";
+const SECONDS_PER_DAY: u64 = 86_400;
+const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+
+// Accepted flag, timestamp in seconds since epoch so it survives restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsEntry {
+    pub timestamp: u64,
+    pub machine_id: u64,
+    pub points: u64,
+    pub own_type: String, // "user", "root" or "unknown" when type was not detected
+}
+
+// Machine user owns count seen in a listing, at most one per day is kept
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OwnsSnapshot {
+    pub timestamp: u64,
+    pub user_owns_count: u64,
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// Missing or unreadable file gives default value
+fn load_json<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    config_dir()
+        .and_then(|dir| fs::read_to_string(dir.join(file_name)).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_json<T: Serialize + ?Sized>(file_name: &str, value: &T) -> Result<(), String> {
+    let dir = config_dir().ok_or("Cannot locate config directory")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(dir.join(file_name), json).map_err(|e| e.to_string())
+}
+
+pub fn load_points_history() -> Vec<PointsEntry> {
+    load_json(POINTS_HISTORY_FILE_NAME)
+}
+
+pub fn save_points_history(history: &[PointsEntry]) -> Result<(), String> {
+    save_json(POINTS_HISTORY_FILE_NAME, history)
+}
+
+// Writeup URL linked by user per machine id
+pub fn load_writeup_urls() -> HashMap<u64, String> {
+    load_json(WRITEUP_URLS_FILE_NAME)
+}
+
+pub fn save_writeup_urls(urls: &HashMap<u64, String>) -> Result<(), String> {
+    save_json(WRITEUP_URLS_FILE_NAME, urls)
+}
+
+// Machine ids bookmarked by user
+pub fn load_bookmarks() -> HashSet<u64> {
+    load_json(BOOKMARKS_FILE_NAME)
+}
+
+pub fn save_bookmarks(ids: &HashSet<u64>) -> Result<(), String> {
+    save_json(BOOKMARKS_FILE_NAME, ids)
+}
+
+// Machine ids user plans to do
+pub fn load_wishlist() -> HashSet<u64> {
+    load_json(WISHLIST_FILE_NAME)
+}
+
+pub fn save_wishlist(ids: &HashSet<u64>) -> Result<(), String> {
+    save_json(WISHLIST_FILE_NAME, ids)
+}
+
+// Difficulty rating per machine id when it was first listed, stands in for rating at release
+pub fn load_first_difficulties() -> HashMap<u64, u64> {
+    load_json(FIRST_DIFFICULTIES_FILE_NAME)
+}
+
+pub fn save_first_difficulties(difficulties: &HashMap<u64, u64>) -> Result<(), String> {
+    save_json(FIRST_DIFFICULTIES_FILE_NAME, difficulties)
+}
+
+// Snapshots per machine id, oldest first
+pub fn load_owns_snapshots() -> HashMap<u64, Vec<OwnsSnapshot>> {
+    load_json(OWNS_SNAPSHOTS_FILE_NAME)
+}
+
+pub fn save_owns_snapshots(snapshots: &HashMap<u64, Vec<OwnsSnapshot>>) -> Result<(), String> {
+    save_json(OWNS_SNAPSHOTS_FILE_NAME, snapshots)
+}
+
+// Replaces snapshot from same day and drops those older than `keep_weeks`
+pub fn record_owns_snapshot(snapshots: &mut Vec<OwnsSnapshot>, snapshot: OwnsSnapshot, keep_weeks: u64) {
+    let same_day = snapshots.last()
+        .is_some_and(|last| last.timestamp / SECONDS_PER_DAY == snapshot.timestamp / SECONDS_PER_DAY);
+    if same_day {
+        snapshots.pop();
+    }
+    snapshots.push(snapshot);
+    let cutoff = snapshot.timestamp.saturating_sub(keep_weeks * SECONDS_PER_WEEK);
+    snapshots.retain(|s| s.timestamp >= cutoff);
+}
+
+// Owns gained per week for last `weeks` weeks ending at `now`, oldest first.
+// None until snapshots span two days, counts before first snapshot are taken as equal to it
+pub fn weekly_deltas(snapshots: &[OwnsSnapshot], now: u64, weeks: usize) -> Option<Vec<u64>> {
+    let first = snapshots.first()?;
+    if snapshots.len() < 2 {
+        return None;
+    }
+    let count_at = |time: u64| {
+        snapshots.iter()
+            .rev()
+            .find(|s| s.timestamp <= time)
+            .unwrap_or(first)
+            .user_owns_count
+    };
+    let boundaries: Vec<u64> = (0..=weeks)
+        .map(|i| now.saturating_sub((weeks - i) as u64 * SECONDS_PER_WEEK))
+        .collect();
+    Some(boundaries.windows(2)
+        .map(|week| count_at(week[1]).saturating_sub(count_at(week[0])))
+        .collect())
+}
+
+// Entries per calendar day (UTC) for last `days` days, oldest first
+pub fn daily_totals(history: &[PointsEntry], days: usize, value: impl Fn(&PointsEntry) -> u64) -> Vec<u64> {
+    let today = unix_now() / SECONDS_PER_DAY;
+    let mut totals = vec![0; days];
+    for entry in history {
+        let age = today.saturating_sub(entry.timestamp / SECONDS_PER_DAY) as usize;
+        if age < days {
+            totals[days - 1 - age] += value(entry);
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 100 * SECONDS_PER_WEEK;
+
+    fn snapshot(timestamp: u64, user_owns_count: u64) -> OwnsSnapshot {
+        OwnsSnapshot { timestamp, user_owns_count }
+    }
+
+    #[test]
+    fn record_replaces_same_day_and_drops_old() {
+        let mut snapshots = vec![snapshot(NOW - 10 * SECONDS_PER_WEEK, 1), snapshot(NOW - SECONDS_PER_DAY, 5)];
+        record_owns_snapshot(&mut snapshots, snapshot(NOW, 7), 9);
+        record_owns_snapshot(&mut snapshots, snapshot(NOW + 60, 8), 9);
+        assert_eq!(snapshots, vec![snapshot(NOW - SECONDS_PER_DAY, 5), snapshot(NOW + 60, 8)]);
+    }
+
+    #[test]
+    fn weekly_deltas_need_history() {
+        assert_eq!(weekly_deltas(&[], NOW, 8), None);
+        assert_eq!(weekly_deltas(&[snapshot(NOW, 5)], NOW, 8), None);
+    }
+
+    #[test]
+    fn weekly_deltas_count_gain_per_week() {
+        let snapshots = [
+            snapshot(NOW - 3 * SECONDS_PER_WEEK, 10),
+            snapshot(NOW - 2 * SECONDS_PER_WEEK + SECONDS_PER_DAY, 14),
+            snapshot(NOW, 20),
+        ];
+        assert_eq!(weekly_deltas(&snapshots, NOW, 4), Some(vec![0, 0, 4, 6]));
+    }
+}
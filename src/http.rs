@@ -0,0 +1,124 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use reqwest::{Client, Request, RequestBuilder, Response};
+use serde::Serialize;
+use crate::config::Config;
+
+// Traffic to HTB API during this session. Sizes are estimated from request line, headers
+// and body in both directions, headers are not compressed on the wire so this overestimates a bit
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AppMetrics {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub requests_made: u64,
+    pub total_request_time: Duration, // Time until response body was read, summed
+}
+
+// Reqwest client with Hackthebox application key attached to every request
+#[derive(Debug, Clone)]
+pub struct HTBClient {
+    pub client: Client,
+    pub api_key: String,
+    metrics: Arc<Mutex<AppMetrics>>, // Shared by clones used in spawned tasks
+}
+
+impl HTBClient {
+    pub fn new(api_key: String, config: &Config) -> Self {
+        // Bounded timeouts so a slow API does not freeze the UI
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self { client, api_key, metrics: Arc::default() }
+    }
+
+    pub fn get(&self, url: &str) -> HTBRequest {
+        self.request(self.client.get(url))
+    }
+
+    pub fn post(&self, url: &str) -> HTBRequest {
+        self.request(self.client.post(url))
+    }
+
+    fn request(&self, builder: RequestBuilder) -> HTBRequest {
+        HTBRequest {
+            builder: builder.header("Authorization", format!("Bearer {}", self.api_key)),
+            metrics: Arc::clone(&self.metrics),
+        }
+    }
+
+    pub fn metrics(&self) -> AppMetrics {
+        *self.metrics.lock().expect("Metrics lock poisoned")
+    }
+}
+
+// Request builder that records traffic in client metrics when sent
+pub struct HTBRequest {
+    builder: RequestBuilder,
+    metrics: Arc<Mutex<AppMetrics>>,
+}
+
+impl HTBRequest {
+    pub fn header(self, key: &str, value: &str) -> Self {
+        Self { builder: self.builder.header(key, value), ..self }
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        Self { builder: self.builder.json(json), ..self }
+    }
+
+    // Body is read here so its real size is known, also for chunked responses
+    pub async fn send(self) -> reqwest::Result<Response> {
+        let (client, request) = self.builder.build_split();
+        let request = request?;
+        let bytes_sent = request_size(&request);
+
+        let started = Instant::now();
+        let mut bytes_received = 0;
+        let response = match client.execute(request).await {
+            Ok(response) => {
+                bytes_received += response_head_size(&response);
+                buffer_body(response).await.inspect(|(_, body_size)| bytes_received += body_size)
+            }
+            Err(e) => Err(e),
+        };
+
+        let mut metrics = self.metrics.lock().expect("Metrics lock poisoned");
+        metrics.requests_made += 1;
+        metrics.bytes_sent += bytes_sent;
+        metrics.bytes_received += bytes_received;
+        metrics.total_request_time += started.elapsed();
+        response.map(|(response, _)| response)
+    }
+}
+
+// Read whole body and rebuild response around it, returns body size too
+async fn buffer_body(response: Response) -> reqwest::Result<(Response, u64)> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    let body_size = body.len() as u64;
+
+    let mut buffered = http::Response::new(body);
+    *buffered.status_mut() = status;
+    *buffered.version_mut() = version;
+    *buffered.headers_mut() = headers;
+    Ok((Response::from(buffered), body_size))
+}
+
+fn request_size(request: &Request) -> u64 {
+    let line = request.method().as_str().len() + request.url().as_str().len() + 11; // " HTTP/1.1\r\n"
+    let body = request.body().and_then(|body| body.as_bytes()).map_or(0, <[u8]>::len);
+    (line + headers_size(request.headers()) + body) as u64
+}
+
+fn response_head_size(response: &Response) -> u64 {
+    (17 + headers_size(response.headers())) as u64 // "HTTP/1.1 200 OK\r\n"
+}
+
+fn headers_size(headers: &reqwest::header::HeaderMap) -> usize {
+    headers.iter().map(|(name, value)| name.as_str().len() + value.len() + 4).sum()
+}
@@ -1,5 +1,12 @@
 pub mod app;
+pub mod calendar;
+pub mod clipboard;
+pub mod command;
+pub mod config;
 pub mod event;
 pub mod handler;
+pub mod history;
+pub mod http;
+pub mod themes;
 pub mod tui;
 pub mod ui;
@@ -2,15 +2,23 @@ use std::{io, env};
 use ratatui::{backend::CrosstermBackend, Terminal};
 
 use crate::{
-    app::{App, AppResult, fetch_all_machines, spawn_machine, submit_flag},
+    app::{App, AppResult, app_error_message, check_api_reachable, download_vpn_config, fetch_all_machines, fetch_machine_detail, fetch_user_id_from_key, fetch_user_profile, ping_machine, spawn_machine, submit_flag, submit_writeup, terminate_machine},
+    config::Config,
     event::{Event, EventHandler},
     handler::handle_key_events,
     tui::Tui,
 };
 
 pub mod app;
+pub mod calendar;
+pub mod clipboard;
+pub mod command;
+pub mod config;
 pub mod event;
 pub mod handler;
+pub mod history;
+pub mod http;
+pub mod themes;
 pub mod tui;
 pub mod ui;
 
@@ -18,6 +26,7 @@ pub mod ui;
 #[tokio::main]
 async fn main() ->AppResult<()> { 
     let htb_api_key = env::var("HTB_API_KEY")?;
+    let config = Config::load();
 
     let backend = CrosstermBackend::new(io::stdout());
     let terminal = Terminal::new(backend)?;
@@ -26,55 +35,108 @@ async fn main() ->AppResult<()> {
     tui.init()?;
 
     let event_sender = tui.events.sender.clone();
-    let mut app = App::new(htb_api_key, event_sender);
+    let mut app = App::new(htb_api_key, config, event_sender);
+    app.request_fetch_user_id();
     app.request_fetch_machines();
 
     while app.running {
         tui.draw(&mut app)?;
         match tui.events.next().await? {
-            Event::Tick => {}
-                //app.tick(),
+            Event::Tick => app.tick(),
             Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
             Event::Mouse(_) => {}
             Event::Resize(_, _) => {}
-            Event::FetchMachines => {
-                let client = app.client.clone();
-                let htb_api_key = app.htb_api_key.clone();
+            Event::FetchUserId => {
+                let client = app.htb_client.clone();
                 let sender = tui.events.sender.clone();
                 tokio::spawn(async move {
-                    let result = fetch_all_machines(&client, &htb_api_key, &sender).await
-                        .map_err(|e| e.to_string());
-                    match result {
-                        Ok(()) => {
-                            sender.send(Event::FetchMachinesResult(Ok((Vec::new(), Ok(()))))).unwrap();
-                        }
-                        Err(e) => {
-                            sender.send(Event::FetchMachinesResult(Ok((Vec::new(), Err(e))))).unwrap();
+                    let result = fetch_user_id_from_key(&client).await
+                        .map_err(|e| app_error_message(e.as_ref()));
+                    sender.send(Event::FetchUserIdResult(result)).unwrap();
+                });
+            }
+            Event::FetchUserIdResult(result) => {
+                app.handle_fetch_user_id_result(result);
+            }
+            Event::FetchUserStats => {
+                if let Some(user_id) = app.user_id {
+                    let client = app.htb_client.clone();
+                    let sender = tui.events.sender.clone();
+                    tokio::spawn(async move {
+                        let result = fetch_user_profile(&client, user_id).await
+                            .map_err(|e| app_error_message(e.as_ref()));
+                        match result {
+                            Ok((stats, achievements)) => {
+                                sender.send(Event::FetchUserStatsResult(Ok(stats))).unwrap();
+                                sender.send(Event::FetchAchievementsResult(Ok(achievements))).unwrap();
+                            }
+                            Err(e) => {
+                                sender.send(Event::FetchUserStatsResult(Err(e))).unwrap();
+                            }
                         }
-                    }
+                    });
+                }
+            }
+            Event::FetchUserStatsResult(result) => {
+                app.handle_fetch_user_stats_result(result);
+            }
+            Event::FetchAchievementsResult(result) => {
+                app.handle_fetch_achievements_result(result);
+            }
+            Event::FetchMachines => {
+                let generation = app.start_machines_fetch();
+                let client = app.htb_client.clone();
+                let sender = tui.events.sender.clone();
+                tokio::spawn(async move {
+                    let result = fetch_all_machines(&client, &sender, generation).await
+                        .map_err(|e| app_error_message(e.as_ref()));
+                    sender.send(Event::FetchMachinesDone(generation, result)).unwrap();
                 });
             }
-            Event::FetchMachinesResult(result) => {
-                app.handle_fetch_machines_result(result);
+            Event::FetchMachinesResult(generation, machines) => {
+                app.handle_fetch_machines_result(generation, machines);
+            }
+            Event::FetchMachinesDone(generation, result) => {
+                app.handle_fetch_machines_done(generation, result);
+            }
+            Event::FetchMachineDetail(machine_id) => {
+                let client = app.htb_client.clone();
+                let sender = tui.events.sender.clone();
+                tokio::spawn(async move {
+                    let result = fetch_machine_detail(&client, machine_id).await;
+                    sender.send(Event::FetchMachineDetailResult(machine_id, result)).unwrap();
+                });
+            }
+            Event::FetchMachineDetailResult(machine_id, result) => {
+                app.handle_fetch_machine_detail_result(machine_id, result);
             }
             Event::SpawnMachine(machine_id) => {
-                let client = app.client.clone();
-                let htb_api_key = app.htb_api_key.clone();
+                let client = app.htb_client.clone();
                 let sender = tui.events.sender.clone();
                 tokio::spawn(async move {
-                    let result = spawn_machine(&client, &htb_api_key, machine_id).await;
+                    let result = spawn_machine(&client, machine_id).await;
                     sender.send(Event::SpawnMachineResult(result)).unwrap();
                 });
             }
             Event::SpawnMachineResult(result) => {
                 app.handle_spawn_machine_result(result);
             }
-            Event::SubmitFlag(machine_id, flag) => {
-                    let client = app.client.clone();
-                    let htb_api_key = app.htb_api_key.clone();
+            Event::TerminateMachine(machine_id) => {
+                let client = app.htb_client.clone();
+                let sender = tui.events.sender.clone();
+                tokio::spawn(async move {
+                    let result = terminate_machine(&client, machine_id).await;
+                    sender.send(Event::TerminateMachineResult(result)).unwrap();
+                });
+            }
+            Event::TerminateMachineResult(result) => {
+                app.handle_terminate_machine_result(result);
+            }
+            Event::SubmitFlag(machine_id, flag, flag_type) => {
+                    let client = app.htb_client.clone();
                     let sender = tui.events.sender.clone();
                     tokio::spawn(async move {
-                        let result = submit_flag(&client, &htb_api_key, machine_id, &flag).await;
+                        let result = submit_flag(&client, machine_id, &flag, flag_type).await;
                         if result.is_ok() {
                             sender.send(Event::UpdateList).unwrap();
                         }
@@ -84,12 +146,59 @@ async fn main() ->AppResult<()> {
             Event::SubmitFlagResult(result) => {
                 app.handle_submit_flag_result(result);
             }
+            Event::SubmitWriteup(machine_id, url) => {
+                let client = app.htb_client.clone();
+                let sender = tui.events.sender.clone();
+                tokio::spawn(async move {
+                    let result = submit_writeup(&client, machine_id, url).await;
+                    sender.send(Event::SubmitWriteupResult(result)).unwrap();
+                });
+            }
+            Event::SubmitWriteupResult(result) => {
+                app.handle_submit_writeup_result(result);
+            }
             Event::UpdateList => {
+                app.request_fetch_user_stats(); // User id stays cached from startup
                 app.request_fetch_machines();
             }
             Event::UpdateInfoMessage(message) => {
                 app.set_info_message(message);
             }
+            Event::DownloadVpnConfig => {
+                let client = app.htb_client.clone();
+                let server_type = app.config.vpn_server_type.clone();
+                let output_path = app.config.vpn_config_path.clone();
+                let sender = tui.events.sender.clone();
+                tokio::spawn(async move {
+                    let result = download_vpn_config(&client, &server_type, &output_path).await
+                        .map_err(|e| app_error_message(e.as_ref()));
+                    sender.send(Event::DownloadVpnConfigResult(result)).unwrap();
+                });
+            }
+            Event::DownloadVpnConfigResult(result) => {
+                app.handle_download_vpn_config_result(result);
+            }
+            Event::PingMachine(ip) => {
+                let sender = tui.events.sender.clone();
+                tokio::spawn(async move {
+                    let result = ping_machine(&ip).await;
+                    sender.send(Event::PingMachineResult(ip, result)).unwrap();
+                });
+            }
+            Event::PingMachineResult(ip, result) => {
+                app.handle_ping_machine_result(ip, result);
+            }
+            Event::CheckReachability => {
+                let client = app.htb_client.clone();
+                let sender = tui.events.sender.clone();
+                tokio::spawn(async move {
+                    let reachable = check_api_reachable(&client).await;
+                    sender.send(Event::CheckReachabilityResult(reachable)).unwrap();
+                });
+            }
+            Event::CheckReachabilityResult(reachable) => {
+                app.handle_check_reachability_result(reachable);
+            }
         }
     }
 
@@ -0,0 +1,77 @@
+use ratatui::style::Color;
+
+// Colors used across the UI, picked with [ui] theme in config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Color,
+    pub foreground: Color,
+    pub highlight: Color,
+    pub active: Color,
+    pub inactive: Color,
+    pub error: Color,
+    pub success: Color,
+    pub warning: Color,
+}
+
+impl Theme {
+    // Default look for dark terminals
+    pub const DARK: Theme = Theme {
+        background: Color::Reset,
+        foreground: Color::White,
+        highlight: Color::Yellow,
+        active: Color::Green,
+        inactive: Color::Red,
+        error: Color::Red,
+        success: Color::Green,
+        warning: Color::Rgb(255, 165, 0),
+    };
+
+    pub const LIGHT: Theme = Theme {
+        background: Color::Reset,
+        foreground: Color::Black,
+        highlight: Color::Blue,
+        active: Color::Rgb(0, 128, 0),
+        inactive: Color::Rgb(178, 34, 34),
+        error: Color::Rgb(178, 34, 34),
+        success: Color::Rgb(0, 128, 0),
+        warning: Color::Rgb(184, 134, 11),
+    };
+
+    pub const SOLARIZED: Theme = Theme {
+        background: Color::Rgb(0, 43, 54),
+        foreground: Color::Rgb(131, 148, 150),
+        highlight: Color::Rgb(181, 137, 0),
+        active: Color::Rgb(133, 153, 0),
+        inactive: Color::Rgb(220, 50, 47),
+        error: Color::Rgb(220, 50, 47),
+        success: Color::Rgb(133, 153, 0),
+        warning: Color::Rgb(203, 75, 22),
+    };
+
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name.to_lowercase().as_str() {
+            "dark" => Some(Theme::DARK),
+            "light" => Some(Theme::LIGHT),
+            "solarized" => Some(Theme::SOLARIZED),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+// Colors assigned to machine categories by hash of category name
+pub const CATEGORY_PALETTE: [Color; 8] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+];
@@ -1,100 +1,105 @@
-use crate::app::App;
+use crate::app::{validate_flag_chars, App, CharStatus, DifficultyTier, FlagType, InputMode, ListDensity, MachineSummary, SortCriteria, SESSION_LIMIT};
+use crate::calendar;
+use crate::history::daily_totals;
+use crate::themes::{Theme, CATEGORY_PALETTE};
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Duration;
 
 use ratatui::{
-    layout::{Constraint, Layout, Rect, Position},
+    layout::{Constraint, Layout, Margin, Rect, Position},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Clear},
+    widgets::{
+        Block, Borders, Gauge, List, ListItem, ListState, Paragraph, Clear, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Sparkline, Wrap,
+    },
     text::{Line, Span},
     Frame,
 };
 
 pub fn render(app: &mut App, frame: &mut Frame) {
+    let theme = app.current_theme;
+    frame.render_widget(Block::default().style(Style::default().bg(theme.background)), frame.area());
+
+    if app.fullscreen_detail {
+        if let Some(machine) = app.highlighted_machine() {
+            render_fullscreen_detail(app, frame, &machine);
+        }
+        return;
+    }
+
     let chunks = 
-        Layout::vertical([Constraint::Percentage(90), 
-            Constraint::Percentage(10)])
+        Layout::vertical([Constraint::Min(0), 
+            Constraint::Length(3),
+            Constraint::Length(1)])
             .split(frame.area());
 
+    let main_chunks =
+        Layout::horizontal([Constraint::Percentage(75), Constraint::Percentage(25)])
+            .split(chunks[0]);
+
     let filtered_machines = app.filtered_machines();
     let sorted_machines = app.sorted_machines(filtered_machines);
 
-    let items: Vec<ListItem> = sorted_machines
-        .iter()
-        .map(|machine| {
-            let status = if machine.is_active() {
-                Span::styled("Active", Style::default().fg(Color::Green))
-            } else {
-                Span::styled("Inactive", Style::default().fg(Color::Red))
-            };
-            let user_owns_symbol = if machine.auth_user_in_user_owns {
-                "✓"
-            } else {
-                " "
-            };
-            let root_owns_symbol = if machine.auth_user_in_root_owns {
-                "✓"
-            } else {
-                " "
-            };
+    // Only build items for visible rows, long lists are slow to render otherwise
+    let visible_count = (main_chunks[0].height.saturating_sub(2) as usize / app.list_density.lines_per_item()).max(1);
+    let selected = app.state.selected();
+    if let Some(selected) = selected {
+        if selected < app.list_offset {
+            app.list_offset = selected;
+        } else if selected >= app.list_offset + visible_count {
+            app.list_offset = selected + 1 - visible_count;
+        }
+    }
+    app.list_offset = app.list_offset.min(sorted_machines.len().saturating_sub(visible_count));
+    let visible_end = (app.list_offset + visible_count).min(sorted_machines.len());
 
-            let line = Line::from(vec![
-                Span::raw(
-                    format!(
-                        "{:15} ({:10}) [{:3}] U:{}, R:{} ",
-                        machine.name,
-                        machine.os,
-                        machine.difficulty,
-                        user_owns_symbol,
-                        root_owns_symbol
-                    )
-                ),
-                status,
-            ]);
-
-            ListItem::new(line).style(Style::default().fg(Color::White))
-        })
+    let items: Vec<ListItem> = sorted_machines[app.list_offset..visible_end]
+        .iter()
+        .map(|machine| machine_list_item(app, machine))
         .collect();
 
     let list_title = format!(
-        "Machines (Filter: {:?}, Sort: {:?})",
-        app.filter_criteria, app.sort_criteria
+        "Machines (Filter: {:?}, Sort: {:?}, then {:?})",
+        app.filter_criteria, app.sort_criteria, app.secondary_sort_criteria
     );
     let list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title(list_title))
         .highlight_style(
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme.highlight)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("> ");
 
-    frame.render_stateful_widget(list, chunks[0], &mut app.state.clone());
+    let mut visible_state = ListState::default().with_selected(selected.map(|i| i - app.list_offset));
+    frame.render_stateful_widget(list, main_chunks[0], &mut visible_state);
+
+    let mut scrollbar_state = ScrollbarState::new(sorted_machines.len())
+        .position(selected.unwrap_or(app.list_offset));
+    frame.render_stateful_widget(
+        Scrollbar::new(ScrollbarOrientation::VerticalRight),
+        main_chunks[0].inner(Margin { vertical: 1, horizontal: 0 }),
+        &mut scrollbar_state,
+    );
+
+    render_stats_panel(app, frame, main_chunks[1]);
+
+    render_tooltip(app, frame);
 
     let info_paragraph = Paragraph::new(app.info_message.clone())
-        .style(Style::default().fg(Color::LightCyan))
+        .style(Style::default().fg(theme.foreground))
         .block(Block::default().borders(Borders::ALL).title("Info"));
 
     frame.render_widget(info_paragraph, chunks[1]);
 
+    render_status_bar(app, frame, chunks[2]);
+
     if app.show_input_field {
         if let Some(selected) = app.state.selected() {
             if selected < sorted_machines.len() {
                 let machine = &sorted_machines[selected];
-                let popup_area = Layout::default()
-                    .direction(ratatui::layout::Direction::Vertical)
-                    .constraints([
-                        Constraint::Percentage(20),
-                        Constraint::Percentage(60),
-                        Constraint::Percentage(20),
-                    ])
-                    .split(frame.area())[1];
-                let popup_area = Layout::default()
-                    .direction(ratatui::layout::Direction::Horizontal)
-                    .constraints([
-                        Constraint::Percentage(20),
-                        Constraint::Percentage(60),
-                        Constraint::Percentage(20),
-                    ])
-                    .split(popup_area)[1];
+                let popup_area = centered_rect(60, 60, frame.area());
 
                 frame.render_widget(Clear, popup_area);
 
@@ -104,7 +109,7 @@ pub fn render(app: &mut App, frame: &mut Frame) {
                         Constraint::Ratio(1,2)])
                         .split(popup_area);
 
-                let active_info = Paragraph::new(vec![
+                let mut info_lines = vec![
                     Line::from(vec![
                         Span::styled(
                             "Active machine: ",
@@ -119,34 +124,846 @@ pub fn render(app: &mut App, frame: &mut Frame) {
                         ),
                         Span::raw(app.selected_machine_ip.as_deref().unwrap_or("N/A")),
                     ]),
-                ])
-                .style(Style::default().fg(Color::White))
+                ];
+                let hostnames = app.selected_machine_ip
+                    .as_deref()
+                    .and_then(|ip| ip.parse().ok())
+                    .and_then(|ip| app.hosts.get(&ip))
+                    .filter(|hostnames| !hostnames.is_empty());
+                if let Some(hostnames) = hostnames {
+                    info_lines.push(Line::from(vec![
+                        Span::styled("Hostnames: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(hostnames.join(", ")),
+                    ]));
+                }
+                info_lines.push(match app.spawn_time.get(&machine.id) {
+                    Some(spawned_at) => Line::from(vec![
+                        Span::styled("Running for: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(format_duration(spawned_at.elapsed())),
+                    ]),
+                    None => Line::from(vec![
+                        Span::styled("Running: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw("(started before session)"),
+                    ]),
+                });
+                info_lines.push(difficulty_line(machine, &theme));
+                if let Some(maker) = app.machine_details_cache.get(&machine.id).and_then(|d| d.maker.as_ref()) {
+                    info_lines.push(Line::from(vec![
+                        Span::styled("Maker: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::raw(maker.name.clone()),
+                    ]));
+                }
+                if let Some(category) = &machine.category {
+                    info_lines.push(Line::from(vec![
+                        Span::styled("Category: ", Style::default().add_modifier(Modifier::BOLD)),
+                        Span::styled(category.clone(), Style::default().fg(category_color(category))),
+                    ]));
+                }
+                if let Some(line) = ping_line(app) {
+                    info_lines.push(line);
+                }
+
+                let active_info = Paragraph::new(info_lines)
+                .style(Style::default().fg(theme.foreground))
                 .block(Block::default().borders(Borders::ALL).title("Active Machine Info"));
 
                 frame.render_widget(active_info, details_chunk[0]);
 
                 let input_chunks =
-                    Layout::vertical([Constraint::Length(3), Constraint::Length(3)]).split(details_chunk[1]);
+                    Layout::vertical([Constraint::Length(3), Constraint::Length(1), Constraint::Length(5)])
+                        .split(details_chunk[1]);
 
                 let flag_block = Paragraph::new(app.flag_input.clone())
                     .style(match app.input_mode {
-                        crate::app::InputMode::Flag => Style::default().fg(Color::Yellow),
-                        _ => Style::default().fg(Color::White),
+                        InputMode::Flag => Style::default().fg(theme.highlight),
+                        _ => Style::default().fg(theme.foreground),
                     })
-                    .block(Block::default().borders(Borders::ALL).title("Flag"));
+                    .block(Block::default().borders(Borders::ALL).title(match app.flag_type {
+                        Some(FlagType::User) => "User Flag",
+                        Some(FlagType::Root) => "Root Flag",
+                        None => "Flag",
+                    }));
 
                 frame.render_widget(flag_block, input_chunks[0]);
+                frame.render_widget(Paragraph::new(flag_preview(&app.flag_input, &theme)), input_chunks[1]);
 
-                match app.input_mode {
-                    crate::app::InputMode::Flag => {
-                        frame.set_cursor_position(Position::new(
-                            input_chunks[0].x + app.flag_input.len() as u16 + 1,
-                            input_chunks[0].y + 1,
-                        ));
-                    }
-                    _ => {}
+                if let Some(trend) = app.solve_trends.get(&machine.id) {
+                    let sparkline = Sparkline::default()
+                        .data(trend)
+                        .style(Style::default().fg(theme.active))
+                        .block(Block::default().borders(Borders::ALL).title("Solves / week (8w)"));
+                    frame.render_widget(sparkline, input_chunks[2]);
                 }
+
+                if app.input_mode == InputMode::Flag {
+                    frame.set_cursor_position(Position::new(
+                        input_chunks[0].x + app.flag_input.len() as u16 + 1,
+                        input_chunks[0].y + 1,
+                    ));
+                }
+            }
+        }
+    }
+
+    if app.input_mode == InputMode::Confirm {
+        let confirm_area = centered_rect(50, 20, frame.area());
+        frame.render_widget(Clear, confirm_area);
+
+        let confirm_paragraph = Paragraph::new(app.confirm_message.clone())
+            .style(Style::default().fg(theme.warning))
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title("Confirm"));
+
+        frame.render_widget(confirm_paragraph, confirm_area);
+    }
+
+    if app.input_mode == InputMode::WriteupUrl {
+        let writeup_area = centered_rect(60, 20, frame.area());
+        frame.render_widget(Clear, writeup_area);
+
+        let writeup_paragraph = Paragraph::new(app.writeup_input.clone())
+            .style(Style::default().fg(theme.highlight))
+            .block(Block::default().borders(Borders::ALL).title("Writeup URL (Enter submit, Esc cancel)"));
+
+        frame.render_widget(writeup_paragraph, writeup_area);
+        frame.set_cursor_position(Position::new(
+            writeup_area.x + app.writeup_input.len() as u16 + 1,
+            writeup_area.y + 1,
+        ));
+    }
+
+    if app.input_mode == InputMode::Calendar {
+        calendar::render(app, frame);
+    }
+
+    if app.input_mode == InputMode::ClipboardHistory {
+        render_clipboard_history(app, frame);
+    }
+
+    if app.input_mode == InputMode::SecondarySort {
+        render_secondary_sort_picker(app, frame);
+    }
+
+    if app.input_mode == InputMode::Achievements {
+        render_achievements(app, frame);
+    }
+
+    if app.input_mode == InputMode::ActivityHeatmap {
+        calendar::render_activity_heatmap(app, frame);
+    }
+
+    if app.input_mode == InputMode::Metrics {
+        render_metrics(app, frame);
+    }
+}
+
+// HTB API traffic this session with request and machine latency
+fn render_metrics(app: &App, frame: &mut Frame) {
+    let theme = app.current_theme;
+    let area = centered_rect(40, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let metrics = app.htb_client.metrics();
+    let average_latency = match metrics.requests_made {
+        0 => "N/A".to_string(),
+        requests => format!("{} ms", metrics.total_request_time.as_millis() / requests as u128),
+    };
+    let ping = match &app.ping_result {
+        Some((ip, Ok(latency))) => format!("{:.1} ms ({})", latency, ip),
+        Some((ip, Err(_))) => format!("unreachable ({})", ip),
+        None => "N/A".to_string(),
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let lines = vec![
+        Line::from(vec![Span::styled("Requests made: ", bold), Span::raw(metrics.requests_made.to_string())]),
+        Line::from(vec![Span::styled("Sent: ", bold), Span::raw(format_bytes(metrics.bytes_sent))]),
+        Line::from(vec![Span::styled("Received: ", bold), Span::raw(format_bytes(metrics.bytes_received))]),
+        Line::from(vec![Span::styled("Average API latency: ", bold), Span::raw(average_latency)]),
+        Line::from(vec![Span::styled("Machine ping: ", bold), Span::raw(ping)]),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.foreground))
+        .block(Block::default().borders(Borders::ALL).title("Connection Metrics (Esc close)"));
+    frame.render_widget(paragraph, area);
+}
+
+// All user achievements with date earned
+fn render_achievements(app: &App, frame: &mut Frame) {
+    let theme = app.current_theme;
+    let area = centered_rect(50, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = app.achievements
+        .iter()
+        .map(|achievement| {
+            let date = achievement.date.get(..10).unwrap_or(&achievement.date).to_string();
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(
+                    "{} {:40} ",
+                    achievement_icon(&achievement.icon_code, app.config.use_unicode_icons),
+                    achievement.name
+                )),
+                Span::styled(date, Style::default().add_modifier(Modifier::DIM)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .style(Style::default().fg(theme.foreground))
+        .block(Block::default().borders(Borders::ALL).title(format!("Achievements ({}, Esc close)", app.achievements.len())))
+        .highlight_style(Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(list, area, &mut app.achievements_state.clone());
+}
+
+// Small list of sort keys used to break ties in primary sort
+fn render_secondary_sort_picker(app: &App, frame: &mut Frame) {
+    let theme = app.current_theme;
+    let area = centered_rect(30, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = SortCriteria::ALL
+        .iter()
+        .map(|criteria| ListItem::new(format!("{:?}", criteria)))
+        .collect();
+
+    let list = List::new(items)
+        .style(Style::default().fg(theme.foreground))
+        .block(Block::default().borders(Borders::ALL).title("Secondary Sort (Enter pick, Esc close)"))
+        .highlight_style(Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(list, area, &mut app.secondary_sort_state.clone());
+}
+
+// Previously copied values with time since copy
+fn render_clipboard_history(app: &App, frame: &mut Frame) {
+    let theme = app.current_theme;
+    let area = centered_rect(50, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = app.clipboard_history
+        .iter()
+        .map(|(value, copied_at)| {
+            let preview: String = if value.chars().count() > 40 {
+                value.chars().take(39).chain(['…']).collect()
+            } else {
+                value.clone()
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:40} ", preview)),
+                Span::styled(format_relative(copied_at.elapsed()), Style::default().add_modifier(Modifier::DIM)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .style(Style::default().fg(theme.foreground))
+        .block(Block::default().borders(Borders::ALL).title("Clipboard History (Enter copy, Esc close)"))
+        .highlight_style(Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(list, area, &mut app.clipboard_state.clone());
+}
+
+// List entry for machine, layout depends on selected list density
+fn machine_list_item<'a>(app: &App, machine: &'a MachineSummary) -> ListItem<'a> {
+    let theme = app.current_theme;
+    let selection_marker = if app.selected_machines.contains(&machine.id) {
+        "* "
+    } else {
+        "  "
+    };
+    let marker = Span::styled(selection_marker, Style::default().fg(theme.highlight));
+
+    let user_owns_symbol = if machine.auth_user_in_user_owns {
+        "✓"
+    } else {
+        " "
+    };
+    let root_owns_symbol = if machine.auth_user_in_root_owns {
+        "✓"
+    } else {
+        " "
+    };
+
+    let status = if machine.is_active() {
+        Span::styled("Active", Style::default().fg(theme.active))
+    } else {
+        Span::styled("Inactive", Style::default().fg(theme.inactive))
+    };
+
+    // Name split around search match and padded to fixed width
+    let mut name_spans = vec![marker, list_marks(app, machine.id)];
+    name_spans.extend(highlight_match(&machine.name, &app.search_query, &theme));
+    let name_padding = " ".repeat(15usize.saturating_sub(machine.name.chars().count()));
+
+    let lines = match app.list_density {
+        ListDensity::Compact => {
+            let status_icon = status_icon(machine.is_active(), app.config.use_unicode_icons);
+            name_spans.push(Span::raw(format!("{} {}", name_padding, status_icon)));
+            vec![Line::from(name_spans)]
+        }
+        ListDensity::Normal => {
+            name_spans.push(Span::raw(
+                format!(
+                    "{} ({}) [{:3}] U:{}, R:{} ",
+                    name_padding,
+                    os_icon(&machine.os, app.config.use_unicode_icons),
+                    machine.difficulty,
+                    user_owns_symbol,
+                    root_owns_symbol
+                )
+            ));
+            name_spans.push(status);
+            vec![Line::from(name_spans)]
+        }
+        ListDensity::Expanded => {
+            name_spans.push(Span::raw(format!(" ({})", machine.os)));
+            let mut lines = vec![
+                Line::from(name_spans).style(Style::default().add_modifier(Modifier::BOLD)),
+                Line::from(vec![
+                    Span::raw(format!(
+                        "    Difficulty: {:3}  U:{}, R:{} ",
+                        machine.difficulty, user_owns_symbol, root_owns_symbol
+                    )),
+                    status,
+                ]),
+            ];
+            match &machine.ip {
+                Some(ip) => lines.push(Line::from(format!("    IP: {}  Points: {}", ip, machine.points))),
+                None => lines.push(Line::from(format!("    Points: {}", machine.points))),
             }
+            lines
         }
+    };
+
+    ListItem::new(lines).style(Style::default().fg(theme.foreground))
+}
+
+// Split text into spans with case-insensitive match of query highlighted
+fn highlight_match<'a>(text: &'a str, query: &str, theme: &Theme) -> Vec<Span<'a>> {
+    if query.is_empty() {
+        return vec![Span::raw(text)];
+    }
+
+    // Lowercase char by char and remember where each char starts in both strings,
+    // lowercasing may change byte length of non-ASCII chars
+    let lowercase_query = query.to_lowercase();
+    let mut lowercase_text = String::new();
+    let mut offsets = Vec::new(); // (offset in lowercase text, offset in text)
+    for (offset, c) in text.char_indices() {
+        offsets.push((lowercase_text.len(), offset));
+        lowercase_text.extend(c.to_lowercase());
+    }
+    offsets.push((lowercase_text.len(), text.len()));
+
+    let original_offset = |lowercase_offset: usize| {
+        offsets.iter().find(|(lowercase, _)| *lowercase == lowercase_offset).map(|(_, offset)| *offset)
+    };
+    let Some(lowercase_start) = lowercase_text.find(&lowercase_query) else {
+        return vec![Span::raw(text)];
+    };
+    let (Some(start), Some(end)) = (original_offset(lowercase_start), original_offset(lowercase_start + lowercase_query.len())) else {
+        return vec![Span::raw(text)]; // Match starts or ends inside a char
+    };
+
+    vec![
+        Span::raw(&text[..start]),
+        Span::styled(&text[start..end], Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)),
+        Span::raw(&text[end..]),
+    ]
+}
+
+// Quick stats of newly selected machine in top-right corner, fades after 2s and hides after 3s
+fn render_tooltip(app: &App, frame: &mut Frame) {
+    let Some((machine, shown_at)) = &app.tooltip_machine else {
+        return;
+    };
+    let elapsed = shown_at.elapsed();
+    if elapsed >= Duration::from_secs(3) {
+        return;
+    }
+    let style = if elapsed >= Duration::from_secs(2) {
+        Style::default().fg(app.current_theme.foreground).add_modifier(Modifier::DIM)
+    } else {
+        Style::default().fg(app.current_theme.foreground)
+    };
+
+    let area = frame.area();
+    let width = 30.min(area.width);
+    let height = 6.min(area.height);
+    let tooltip_area = Rect::new(area.x + area.width - width, area.y, width, height);
+
+    let tooltip = Paragraph::new(vec![
+        Line::from(format!("Tier: {:?}", DifficultyTier::from_difficulty(machine.difficulty))),
+        Line::from(format!("Points: {}  Stars: {:.1}", machine.points, machine.star)),
+        Line::from(format!("User owns: {}", machine.user_owns_count)),
+        Line::from(format!("Root owns: {}", machine.root_owns_count)),
+    ])
+    .style(style)
+    .block(Block::default().borders(Borders::ALL).title(machine.name.clone()));
+
+    frame.render_widget(Clear, tooltip_area);
+    frame.render_widget(tooltip, tooltip_area);
+}
+
+// Whole screen view of highlighted machine with profile synopsis
+fn render_fullscreen_detail(app: &App, frame: &mut Frame, machine: &MachineSummary) {
+    let theme = app.current_theme;
+    let detail = app.machine_details_cache.get(&machine.id);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let owned = |owns: bool| if owns { "✓" } else { "✗" };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("OS: ", bold),
+            Span::raw(format!("{} {}", os_icon(&machine.os, app.config.use_unicode_icons), machine.os)),
+        ]),
+        difficulty_line(machine, &theme),
+        Line::from(vec![
+            Span::styled("Difficulty chart: ", bold),
+            Span::styled(
+                "█".repeat((machine.difficulty.clamp(0, 100) / 5) as usize),
+                Style::default().fg(theme.highlight),
+            ),
+            Span::raw(format!(
+                " {:?}{}",
+                DifficultyTier::from_difficulty(machine.difficulty),
+                detail.and_then(|d| d.difficulty_text.as_ref()).map(|t| format!(" ({})", t)).unwrap_or_default()
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled("Points: ", bold),
+            Span::raw(format!("{}  Stars: {:.1}", machine.points, machine.star)),
+        ]),
+        Line::from(vec![
+            Span::styled("Released: ", bold),
+            Span::raw(machine.release_parsed().map_or("N/A".to_string(), |d| format!("{}-{:02}-{:02}", d.year, d.month, d.day))),
+        ]),
+        Line::from(vec![
+            Span::styled("User owns: ", bold),
+            Span::raw(format!("{} {}  ", machine.user_owns_count, owned(machine.auth_user_in_user_owns))),
+            Span::styled("Root owns: ", bold),
+            Span::raw(format!("{} {}", machine.root_owns_count, owned(machine.auth_user_in_root_owns))),
+        ]),
+    ];
+    if let Some(maker) = detail.and_then(|d| d.maker.as_ref()) {
+        lines.push(Line::from(vec![Span::styled("Maker: ", bold), Span::raw(maker.name.clone())]));
+    }
+    if let Some(category) = &machine.category {
+        lines.push(Line::from(vec![
+            Span::styled("Category: ", bold),
+            Span::styled(category.clone(), Style::default().fg(category_color(category))),
+        ]));
+    }
+    if let Some(ip) = &machine.ip {
+        lines.push(Line::from(vec![Span::styled("IP Address: ", bold), Span::raw(ip.clone())]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::styled("Synopsis", bold));
+    lines.push(Line::from(match detail {
+        Some(detail) => detail.synopsis.clone().unwrap_or_else(|| "No synopsis available".to_string()),
+        None => "Loading profile...".to_string(),
+    }));
+
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.foreground))
+        .wrap(Wrap { trim: false })
+        .scroll((app.detail_scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} (j/k scroll, F or Esc back)", machine.name)),
+        );
+    frame.render_widget(paragraph, frame.area());
+}
+
+// User progress through machine catalog
+fn render_stats_panel(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = app.current_theme;
+    let title = match &app.user_stats {
+        Some(stats) => format!("User Stats: {} ({}, {} pts)", stats.name, stats.rank, stats.points),
+        None => "User Stats".to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let gauge_chunks = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(4),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(2),
+        Constraint::Length(1),
+        Constraint::Length(6),
+        Constraint::Min(0),
+    ])
+    .split(inner);
+
+    let gauges = [
+        ("Overall", app.completion_ratio(None), theme.highlight, gauge_chunks[0]),
+        ("Active", app.completion_ratio(Some(false)), theme.active, gauge_chunks[2]),
+        ("Retired", app.completion_ratio(Some(true)), theme.success, gauge_chunks[4]),
+    ];
+
+    for (label, ratio, color, chunk) in gauges {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(color).bg(theme.background))
+            .ratio(ratio)
+            .label(format!("{}: {:.1}%", label, ratio * 100.0));
+        frame.render_widget(gauge, chunk);
+    }
+
+    frame.render_widget(os_distribution(app, gauge_chunks[6].width), gauge_chunks[6]);
+
+    let points = Line::from(vec![
+        Span::styled(format!("Session: +{} pts", app.session_points), Style::default().fg(theme.success)),
+        Span::raw(format!("  All-time: {} pts", app.all_time_points())),
+    ]);
+    frame.render_widget(Paragraph::new(points), gauge_chunks[8]);
+    let daily_points = daily_totals(&app.points_history, 30, |entry| entry.points);
+    frame.render_widget(
+        Sparkline::default().data(&daily_points).style(Style::default().fg(theme.success)),
+        gauge_chunks[9],
+    );
+
+    if !app.achievements.is_empty() {
+        let mut lines = vec![Line::styled("Recent achievements", Style::default().add_modifier(Modifier::BOLD))];
+        lines.extend(app.achievements.iter().take(5).map(|achievement| {
+            Line::from(format!(
+                "{} {}",
+                achievement_icon(&achievement.icon_code, app.config.use_unicode_icons),
+                achievement.name
+            ))
+        }));
+        frame.render_widget(Paragraph::new(lines), gauge_chunks[11]);
+    }
+
+    if let Some(error) = &app.user_id_error {
+        let warning = Paragraph::new(format!("⚠ User ID unavailable: {}", error))
+            .style(Style::default().fg(theme.warning))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(warning, gauge_chunks[12]);
+    }
+}
+
+// Text bars of machines per OS, e.g. "Linux   ████████ 58%"
+fn os_distribution(app: &App, width: u16) -> Paragraph<'static> {
+    let (linux, windows, other) = app.os_distribution();
+    let total = (linux + windows + other).max(1);
+    let bar_width = width.saturating_sub(13) as usize; // Label and percentage
+
+    let mut lines = vec![Line::styled("OS distribution", Style::default().add_modifier(Modifier::BOLD))];
+    for (label, count, color) in [
+        ("Linux", linux, Color::Green),
+        ("Windows", windows, Color::Blue),
+        ("Other", other, Color::Yellow),
+    ] {
+        let filled = (count * bar_width).div_ceil(total).min(bar_width);
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:8}", label)),
+            Span::styled("█".repeat(filled), Style::default().fg(color)),
+            Span::raw(format!(" {}%", count * 100 / total)),
+        ]));
+    }
+    Paragraph::new(lines)
+}
+
+// Flag typed so far with each character colored by validity
+fn flag_preview(flag: &str, theme: &Theme) -> Line<'static> {
+    let spans: Vec<Span> = flag.chars()
+        .zip(validate_flag_chars(flag))
+        .map(|(c, status)| {
+            let color = match status {
+                CharStatus::Valid => theme.success,
+                CharStatus::Invalid => theme.error,
+            };
+            // Show invisible characters such as zero-width space as placeholder
+            let invisible = c.is_control() || c.is_whitespace() || matches!(c, '\u{200B}'..='\u{200D}' | '\u{FEFF}');
+            let shown = if invisible { '·' } else { c };
+            Span::styled(shown.to_string(), Style::default().fg(color))
+        })
+        .collect();
+    Line::from(spans)
+}
+
+// Difficulty with drift since release, e.g. "Difficulty: 45 (↑5 from release)"
+fn difficulty_line(machine: &MachineSummary, theme: &Theme) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled("Difficulty: ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(machine.difficulty.to_string()),
+    ];
+
+    match machine.difficulty_drift() {
+        Some(drift) if drift > 0 => spans.push(Span::styled(
+            format!(" (↑{} from release)", drift),
+            Style::default().fg(theme.warning),
+        )),
+        Some(drift) if drift < 0 => spans.push(Span::styled(
+            format!(" (↓{} from release)", -drift),
+            Style::default().fg(theme.success),
+        )),
+        _ => {}
+    }
+
+    Line::from(spans)
+}
+
+// Stable color per category name
+fn category_color(category: &str) -> Color {
+    let mut hasher = DefaultHasher::new();
+    category.hash(&mut hasher);
+    CATEGORY_PALETTE[(hasher.finish() % CATEGORY_PALETTE.len() as u64) as usize]
+}
+
+// Latency to active machine when auto ping measured current IP
+fn ping_line(app: &App) -> Option<Line<'static>> {
+    let (ip, result) = app.ping_result.as_ref()?;
+    if app.selected_machine_ip.as_ref() != Some(ip) {
+        return None;
+    }
+
+    let theme = app.current_theme;
+    let value = match result {
+        Ok(latency) if *latency < 50.0 => Span::styled(format!("{:.0}ms", latency), Style::default().fg(theme.success)),
+        Ok(latency) if *latency < 100.0 => Span::styled(format!("{:.0}ms", latency), Style::default().fg(theme.warning)),
+        Ok(latency) => Span::styled(format!("{:.0}ms", latency), Style::default().fg(theme.error)),
+        Err(e) => Span::styled(e.clone(), Style::default().fg(theme.error)),
+    };
+
+    Some(Line::from(vec![
+        Span::styled("Ping: ", Style::default().add_modifier(Modifier::BOLD)),
+        value,
+    ]))
+}
+
+// Single line of indicators below info pane
+fn render_status_bar(app: &App, frame: &mut Frame, area: Rect) {
+    let theme = app.current_theme;
+
+    // Command bar replaces indicators while typing a command, like vim's ex mode
+    if app.input_mode == InputMode::Command {
+        frame.render_widget(Paragraph::new(format!(":{}", app.command_input)), area);
+        frame.set_cursor_position(Position::new(
+            area.x + app.command_input.len() as u16 + 1,
+            area.y,
+        ));
+        return;
+    }
+    if app.input_mode == InputMode::Search {
+        frame.render_widget(Paragraph::new(format!("/{}", app.search_query)), area);
+        frame.set_cursor_position(Position::new(
+            area.x + app.search_query.len() as u16 + 1,
+            area.y,
+        ));
+        return;
+    }
+    if let Some(error) = &app.command_error {
+        frame.render_widget(
+            Paragraph::new(error.clone()).style(Style::default().fg(theme.error)),
+            area,
+        );
+        return;
+    }
+
+    let mut indicators: Vec<Span> = Vec::new();
+
+    let session_elapsed = app.session_start.elapsed();
+    let session_color = if session_elapsed >= SESSION_LIMIT {
+        theme.warning
+    } else {
+        theme.foreground
+    };
+    indicators.push(Span::styled(
+        format!(" Session: {} ", format_duration(session_elapsed)),
+        Style::default().fg(session_color),
+    ));
+
+    if !app.search_query.is_empty() {
+        indicators.push(Span::styled(
+            format!(" Search: {} ", app.search_query),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+
+    if let Some((year, month)) = app.release_month_filter {
+        indicators.push(Span::styled(
+            format!(" Released: {}-{:02} ", year, month),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+
+    if let Some(region) = &app.region_filter {
+        indicators.push(Span::styled(
+            format!(" Region: {} ", region),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+
+    if !app.api_reachable {
+        indicators.push(Span::styled(" Offline ", Style::default().fg(theme.error)));
+    }
+
+    if !app.pending_actions.is_empty() {
+        indicators.push(Span::styled(
+            format!(" {} action(s) queued ", app.pending_actions.len()),
+            Style::default().fg(theme.warning),
+        ));
+    }
+
+    if !app.selected_machines.is_empty() {
+        indicators.push(Span::styled(
+            format!(" {} selected ", app.selected_machines.len()),
+            Style::default().fg(theme.background).bg(theme.highlight),
+        ));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(indicators)), area);
+}
+
+// Compact duration like "1h 23m" or "5m"
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+// Human readable size like "512 B", "1.2 KB" or "3.4 MB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+// Time since event like "just now", "2 mins ago" or "3 hours ago"
+pub fn format_relative(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_string(),
+        1 => "1 min ago".to_string(),
+        2..=59 => format!("{} mins ago", minutes),
+        60..=119 => "1 hour ago".to_string(),
+        _ => format!("{} hours ago", minutes / 60),
+    }
+}
+
+// Short OS marker for list items, abbreviation when unicode icons are disabled
+fn os_icon(os: &str, use_unicode_icons: bool) -> &'static str {
+    match (os.to_lowercase().as_str(), use_unicode_icons) {
+        ("linux", true) => "🐧",
+        ("windows", true) => "🪟",
+        (_, true) => "❓",
+        ("linux", false) => "Lin",
+        ("windows", false) => "Win",
+        (_, false) => "Oth",
+    }
+}
+
+// Bookmark and wishlist marks, padded so names stay aligned
+fn list_marks(app: &App, machine_id: u64) -> Span<'static> {
+    let unicode = app.config.use_unicode_icons;
+    let bookmark = match (app.bookmarks.contains(&machine_id), unicode) {
+        (true, true) => "★",
+        (true, false) => "B",
+        (false, _) => " ",
+    };
+    let wishlist = match (app.wishlist.contains(&machine_id), unicode) {
+        (true, true) => "♥",
+        (true, false) => "W",
+        (false, _) => " ",
+    };
+    Span::styled(format!("{}{} ", bookmark, wishlist), Style::default().fg(app.current_theme.warning))
+}
+
+fn status_icon(active: bool, use_unicode_icons: bool) -> &'static str {
+    match (active, use_unicode_icons) {
+        (true, true) => "🟢",
+        (false, true) => "🔴",
+        (true, false) => "On",
+        (false, false) => "Off",
+    }
+}
+
+fn achievement_icon(icon_code: &str, use_unicode_icons: bool) -> &'static str {
+    if !use_unicode_icons {
+        return "*";
+    }
+    let code = icon_code.to_lowercase();
+    if code.contains("blood") {
+        "🩸"
+    } else if code.contains("rank") || code.contains("trophy") {
+        "🏆"
+    } else if code.contains("streak") {
+        "🔥"
+    } else if code.contains("machine") {
+        "💻"
+    } else if code.contains("challenge") {
+        "🧩"
+    } else {
+        "🏅"
+    }
+}
+
+// Area centered in `area` taking given percentage of width and height
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let margin_y = (100 - percent_y) / 2;
+    let margin_x = (100 - percent_x) / 2;
+    let vertical = Layout::vertical([
+        Constraint::Percentage(margin_y),
+        Constraint::Percentage(percent_y),
+        Constraint::Percentage(margin_y),
+    ])
+    .split(area)[1];
+    Layout::horizontal([
+        Constraint::Percentage(margin_x),
+        Constraint::Percentage(percent_x),
+        Constraint::Percentage(margin_x),
+    ])
+    .split(vertical)[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlighted(text: &str, query: &str) -> Vec<String> {
+        highlight_match(text, query, &Theme::DARK)
+            .into_iter()
+            .map(|span| span.content.into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn highlight_match_is_case_insensitive() {
+        assert_eq!(highlighted("Lame", "AM"), vec!["L", "am", "e"]);
+        assert_eq!(highlighted("Lame", "xyz"), vec!["Lame"]);
+    }
+
+    #[test]
+    fn highlight_match_handles_length_changing_lowercase() {
+        // 'İ' is 2 bytes but lowercases to 3
+        assert_eq!(highlighted("İstanbul", "stan"), vec!["İ", "stan", "bul"]);
+        assert_eq!(highlighted("ÅBC", "åb"), vec!["", "ÅB", "C"]);
+        assert_eq!(highlighted("İx", "i"), vec!["İx"]); // Match inside lowercased char
     }
 }